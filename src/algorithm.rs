@@ -1,145 +1,1147 @@
-use std::cmp::{min, Reverse};
-use std::collections::HashMap;
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
 use priority_queue::PriorityQueue;
-use crate::models::{City, Road};
+use crate::models::{City, Direction, Mode, Railway, Restriction, Road};
 
 
 const INF: i32 = 10_i32.pow(9);
 
-pub fn build_graph(nodes: Vec<City>, edges: Vec<Road>) -> HashMap<String, Vec<(i32, String)>> {
-    let mut graph: HashMap<String, Vec<(i32, String)>>  = HashMap::new();
+/// Road or railway leading out of a city in the adjacency map
+#[derive(Debug, Clone)]
+pub struct Edge {
+    // Id of the road, or of the railway for `Mode::Rail` edges
+    pub road_id: i32,
+    pub mode: Mode,
+    pub length: i32,
+    // Travel time in seconds
+    pub duration: i32,
+    pub toll: f64,
+    // What the searches minimise: length, duration or cost in cents, depending on `GraphOptions::optimize`
+    pub weight: i32,
+    pub to: String,
+}
+
+/// City name -> roads leading out of it
+pub type Graph = HashMap<String, Vec<Edge>>;
+
+/// City name -> (previous city and the road taken from it, cost from start)
+pub type PathMap = HashMap<String, (Option<(String, Edge)>, i32)>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Optimize {
+    Distance,
+    Time,
+    // Fuel and tolls, roads only
+    Cost,
+}
+
+impl Optimize {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Optimize::Distance => "distance",
+            Optimize::Time => "time",
+            Optimize::Cost => "cost",
+        }
+    }
+}
+
+/// Speeds in km/h used to estimate travel time
+#[derive(Debug, Clone, PartialEq)]
+pub struct Speeds {
+    // Kilometres in one unit of length
+    pub length_unit_km: f64,
+    // Roads with neither travel time, speed limit nor a known class
+    pub default_road: f64,
+    pub rail: f64,
+    // Road class -> speed
+    pub classes: HashMap<String, f64>,
+}
+
+impl Speeds {
+    ///
+    /// Travel time in seconds: explicit `travel_time`, otherwise length at the speed limit,
+    /// the speed of the road class or the default speed
+    pub fn road_duration(&self, road: &Road) -> i32 {
+        if let Some(minutes) = road.travel_time {
+            return minutes.saturating_mul(60);
+        }
+        let speed = road.speed_limit
+            .filter(|&speed| speed > 0)
+            .map(|speed| speed as f64)
+            .or_else(|| road.road_class.as_ref().and_then(|class| self.classes.get(class).copied()))
+            .unwrap_or(self.default_road);
+        self.duration(road.length, speed)
+    }
+
+    pub fn rail_duration(&self, length: i32) -> i32 {
+        self.duration(length, self.rail)
+    }
+
+    fn duration(&self, length: i32, speed: f64) -> i32 {
+        (length as f64 * self.length_unit_km / speed * 3600.0).round() as i32
+    }
+}
+
+/// Fuel spending of road trips
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fuel {
+    // Price of a litre
+    pub price: f64,
+    // Litres per 100 km
+    pub consumption: f64,
+}
+
+impl Fuel {
+    pub fn cost(&self, km: f64) -> f64 {
+        km * self.consumption / 100.0 * self.price
+    }
+}
+
+// Largest fuel price, fuel consumption, road toll, length and travel time (minutes) accepted from requests.
+// Anything above is a typo, and keeps path costs far from overflowing
+pub const MAX_FUEL_PRICE: f64 = 1_000.0;
+pub const MAX_FUEL_CONSUMPTION: f64 = 1_000.0;
+pub const MAX_TOLL: f64 = 1_000_000.0;
+pub const MAX_LENGTH: i32 = 100_000;
+pub const MAX_TRAVEL_TIME: i32 = 100_000;
+
+/// Size and load of a vehicle, checked against road limits
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VehicleProfile {
+    // Tonnes
+    pub weight: Option<f64>,
+    // Metres
+    pub height: Option<f64>,
+    pub hazmat: bool,
+    // km/h the vehicle can't go faster than
+    pub speed: Option<f64>,
+}
+
+impl VehicleProfile {
+    pub fn allows(&self, road: &Road) -> bool {
+        self.restriction(road).is_none()
+    }
+
+    ///
+    /// Tightest limit of `road` the vehicle exceeds
+    ///
+    /// returns: Option<(f64, Restriction)> => How many times the limit is exceeded (infinite for a hazmat ban)
+    /// and the limit itself, None if the vehicle may use the road
+    ///
+    pub fn restriction(&self, road: &Road) -> Option<(f64, Restriction)> {
+        let road_id = road.id.expect("REASON");
+        let exceeded = |constraint: &str, limit: Option<f64>, value: Option<f64>| match (limit, value) {
+            (Some(limit), Some(value)) if value > limit => Some((value / limit, Restriction {
+                road_id,
+                constraint: constraint.to_string(),
+                limit: Some(limit),
+                vehicle: Some(value),
+            })),
+            _ => None,
+        };
+        let hazmat = if self.hazmat && !road.hazmat_allowed {
+            Some((f64::INFINITY, Restriction { road_id, constraint: "hazmat".to_string(), limit: None, vehicle: None }))
+        } else {
+            None
+        };
+
+        [hazmat, exceeded("weight", road.max_weight, self.weight), exceeded("height", road.max_height, self.height)]
+            .into_iter()
+            .flatten()
+            .max_by(|(a, _), (b, _)| a.total_cmp(b))
+    }
+}
+
+/// What goes into the graph and how edges are weighted
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphOptions {
+    pub modes: Vec<Mode>,
+    pub optimize: Optimize,
+    pub speeds: Speeds,
+    pub fuel: Fuel,
+    // Leave roads with a toll out of the graph
+    pub avoid_tolls: bool,
+    // Cities kept in the graph without any road or railway, by name
+    pub avoid_cities: HashSet<String>,
+    // Roads left out of the graph, by id
+    pub avoid_roads: HashSet<i32>,
+    // Only roads this vehicle may use go into the graph
+    pub vehicle: Option<VehicleProfile>,
+}
+
+impl GraphOptions {
+    /// Road network weighted by distance
+    pub fn new(speeds: Speeds, fuel: Fuel) -> Self {
+        GraphOptions {
+            modes: vec![Mode::Road],
+            optimize: Optimize::Distance,
+            speeds,
+            fuel,
+            avoid_tolls: false,
+            avoid_cities: HashSet::new(),
+            avoid_roads: HashSet::new(),
+            vehicle: None,
+        }
+    }
+
+    fn edge(&self, road_id: i32, mode: Mode, length: i32, duration: i32, toll: f64, to: &str) -> Edge {
+        let weight = match self.optimize {
+            Optimize::Distance => length,
+            Optimize::Time => duration,
+            Optimize::Cost => {
+                let fuel = self.fuel.cost(length as f64 * self.speeds.length_unit_km);
+                ((fuel + toll) * 100.0).round() as i32
+            }
+        };
+        Edge { road_id, mode, length, duration, toll, weight, to: to.to_string() }
+    }
+}
+
+
+///
+/// Adjacency map of the road and railway networks. One-way roads only lead in their `direction`.
+/// Avoided cities stay in the map, but nothing leads in or out of them.
+///
+/// returns: Result<Graph, String> => Error if a road references a city missing from `nodes`
+///
+pub fn build_graph(nodes: &[City], roads: &[Road], railways: &[Railway], options: &GraphOptions)
+    -> Result<Graph, String> {
+    let mut graph: Graph = HashMap::new();
     let mut cities: HashMap<i32, String>  = HashMap::new();
 
-    for city in nodes.clone() {
+    for city in nodes {
         cities.insert(city.id.expect("REASON"), city.name.clone());
-        graph.insert(city.name, Vec::new());
+        graph.insert(city.name.clone(), Vec::new());
     }
-    // for (k, v) in cities.clone() {
-    //     println!("{} || {}", k, v);
-    // }
 
-    for road in edges.clone() {
-        let city_a = cities.get(&road.city_a).unwrap();
-        let city_b = cities.get(&road.city_b).unwrap();
-        // println!("-------");
-        // println!("{} || {}", city_a, city_b);
-        let mut roads_a = graph.get(city_a).unwrap().clone();
-        let mut roads_b = graph.get(city_b).unwrap().clone();
+    let endpoints = |kind: &str, id: i32, a: i32, b: i32| match (cities.get(&a), cities.get(&b)) {
+        (Some(city_a), Some(city_b)) => Ok((city_a.clone(), city_b.clone())),
+        (None, _) => Err(format!("{} {} references unknown city {}", kind, id, a)),
+        (_, None) => Err(format!("{} {} references unknown city {}", kind, id, b)),
+    };
+    let avoided = |city: &String| options.avoid_cities.contains(city);
 
-        roads_a.push((road.length, city_b.clone()));
-        roads_b.push((road.length, city_a.clone()));
+    if options.modes.contains(&Mode::Road) {
+        let passable = |road: &Road| {
+            (!options.avoid_tolls || road.toll <= 0.0)
+                && options.vehicle.as_ref().is_none_or(|vehicle| vehicle.allows(road))
+        };
+        for road in roads.iter().filter(|road| passable(road)) {
+            let road_id = road.id.expect("REASON");
+            let (city_a, city_b) = endpoints("road", road_id, road.city_a, road.city_b)?;
+            if options.avoid_roads.contains(&road_id) || avoided(&city_a) || avoided(&city_b) {
+                continue;
+            }
+            let mut duration = options.speeds.road_duration(road);
+            if let Some(speed) = options.vehicle.as_ref().and_then(|vehicle| vehicle.speed) {
+                duration = duration.max(options.speeds.duration(road.length, speed));
+            }
 
-        graph.insert(city_a.clone(), roads_a.clone());
-        graph.insert(city_b.clone(), roads_b.clone());
+            if road.direction != Direction::BToA {
+                let edge = options.edge(road_id, Mode::Road, road.length, duration, road.toll, &city_b);
+                graph.get_mut(&city_a).unwrap().push(edge);
+            }
+            if road.direction != Direction::AToB {
+                let edge = options.edge(road_id, Mode::Road, road.length, duration, road.toll, &city_a);
+                graph.get_mut(&city_b).unwrap().push(edge);
+            }
+        }
     }
-    graph
+
+    if options.modes.contains(&Mode::Rail) {
+        for railway in railways {
+            let railway_id = railway.id.expect("REASON");
+            let (city_a, city_b) = endpoints("railway", railway_id, railway.city_a, railway.city_b)?;
+            if avoided(&city_a) || avoided(&city_b) {
+                continue;
+            }
+            let duration = options.speeds.rail_duration(railway.length);
+
+            let edge = options.edge(railway_id, Mode::Rail, railway.length, duration, 0.0, &city_b);
+            graph.get_mut(&city_a).unwrap().push(edge);
+            let edge = options.edge(railway_id, Mode::Rail, railway.length, duration, 0.0, &city_a);
+            graph.get_mut(&city_b).unwrap().push(edge);
+        }
+    }
+    Ok(graph)
 }
 
+
+///
+/// Connected components, ignoring road directions
+///
+/// returns: HashMap<String, usize> => Component number of every city, numbered in order of city names
+///
+pub fn connected_components(graph: &Graph) -> HashMap<String, usize> {
+    let mut neighbours: HashMap<&String, Vec<&String>> = HashMap::new();
+    for (city, edges) in graph {
+        for edge in edges {
+            neighbours.entry(city).or_default().push(&edge.to);
+            neighbours.entry(&edge.to).or_default().push(city);
+        }
+    }
+
+    let mut names: Vec<&String> = graph.keys().collect();
+    names.sort();
+    let mut component: HashMap<String, usize> = HashMap::new();
+    let mut count = 0;
+    for name in names {
+        if component.contains_key(name) { continue };
+        let mut stack = vec![name];
+        component.insert(name.clone(), count);
+        while let Some(city) = stack.pop() {
+            for &next in neighbours.get(city).into_iter().flatten() {
+                if !component.contains_key(next) {
+                    component.insert(next.clone(), count);
+                    stack.push(next);
+                }
+            }
+        }
+        count += 1;
+    }
+    component
+}
+
+/// Graph as an N×N matrix of the cheapest edge between every two cities
+#[derive(Debug)]
+pub struct DenseGraph {
+    pub names: Vec<String>,
+    pub index: HashMap<String, usize>,
+    // [from][to] -> cheapest edge, None if the cities aren't linked
+    pub matrix: Vec<Vec<Option<Edge>>>,
+}
+
+impl DenseGraph {
+    pub fn from_graph(graph: &Graph) -> Self {
+        let mut names: Vec<String> = graph.keys().cloned().collect();
+        names.sort();
+        let index: HashMap<String, usize> = names.iter()
+            .enumerate()
+            .map(|(i, name)| (name.clone(), i))
+            .collect();
+        let mut matrix = vec![vec![None; names.len()]; names.len()];
+        for (from, name) in names.iter().enumerate() {
+            for edge in &graph[name] {
+                let cell: &mut Option<Edge> = &mut matrix[from][index[&edge.to]];
+                if cell.as_ref().is_none_or(|old| edge.weight < old.weight) {
+                    *cell = Some(edge.clone());
+                }
+            }
+        }
+        DenseGraph { names, index, matrix }
+    }
+
+    /// Paths from `start` to every reachable city in the `dijkstra_all` format
+    pub fn paths(&self, start: &str) -> PathMap {
+        let (costs, previous) = dijkstra_dense(&self.matrix, self.index[start]);
+        let mut paths = PathMap::new();
+        for (node, &cost) in costs.iter().enumerate() {
+            if cost == INF {
+                continue;
+            }
+            let leg = previous[node].map(|prev| {
+                (self.names[prev].clone(), self.matrix[prev][node].clone().unwrap())
+            });
+            paths.insert(self.names[node].clone(), (leg, cost));
+        }
+        paths
+    }
+}
+
+
+/// Share of ordered pairs of cities linked directly, from 0 to 1
+pub fn density(graph: &Graph) -> f64 {
+    let n = graph.len();
+    if n < 2 {
+        return 0.0;
+    }
+    let links: usize = graph.iter()
+        .map(|(from, edges)| {
+            edges.iter()
+                .filter(|edge| edge.to != *from)
+                .map(|edge| &edge.to)
+                .collect::<HashSet<_>>()
+                .len()
+        })
+        .sum();
+    links as f64 / (n * (n - 1)) as f64
+}
+
+
 ///
 /// # Dijkstra algorithm for dense graphs
-/// O(N^2)
+/// O(N^2), no priority queue
 /// # Arguments
 ///
-/// * `n`<i32>: count of all nodes
-/// * `start`<i32>: starting node
-/// * `dist`<Vec<i32>>: adjacency list with Pairs (Connected node, distance to node)
+/// * `matrix`<&[Vec<Option<Edge>>]>: N×N adjacency matrix, `matrix[from][to]` is the edge between the nodes if any
+/// * `start`<usize>: starting node
 ///
-/// returns: Vec<i32> => Min distance to every node from 'start'
+/// returns: (Vec<i32>, Vec<Option<usize>>) => Min cost to every node from `start` (INF if unreachable)
+/// and the node before it on the shortest path
 ///
-pub fn dijkstra_cost(n: usize, start:usize, dist: Vec<i32>) -> Vec<i32> {
-    let mut result: Vec<i32> = vec![INF; n ];
+pub fn dijkstra_dense(matrix: &[Vec<Option<Edge>>], start: usize) -> (Vec<i32>, Vec<Option<usize>>) {
+    let n = matrix.len();
+    let mut result: Vec<i32> = vec![INF; n];
+    let mut previous: Vec<Option<usize>> = vec![None; n];
     let mut visited: Vec<bool> = vec![false; n];
     result[start] = 0;
 
     for _ in 0..n {
-        let mut curent: i32 = -1;
-        for j in 0..n {
-            if !visited[j] && (curent == -1 || result[j] < result[curent as usize]) {
-                curent = j as i32;
+        let current = match (0..n).filter(|&j| !visited[j] && result[j] < INF).min_by_key(|&j| result[j]) {
+            Some(current) => current,
+            None => break,
+        };
+        visited[current] = true;
+        for (index, edge) in matrix[current].iter().enumerate() {
+            if let Some(edge) = edge {
+                let new_cost = result[current].saturating_add(edge.weight);
+                if new_cost < result[index] {
+                    result[index] = new_cost;
+                    previous[index] = Some(current);
+                }
+            }
+        }
+    }
+    (result, previous)
+}
+
+
+///
+/// Dijkstra on HashMap
+///
+/// returns: (PathMap, usize) => Paths found and count of expanded nodes
+///
+pub fn dijkstra(start: &str, goal: &str, graph: &Graph) -> (PathMap, usize) {
+    search(start, Some(goal), None, graph, |_| 0, |_, _| true)
+}
+
+
+///
+/// Dijkstra on HashMap that never enters the `blocked` cities nor takes the `removed` (city, road id) edges
+///
+/// returns: PathMap => Paths found
+///
+pub fn dijkstra_restricted(start: &str, goal: &str, graph: &Graph,
+                           blocked: &HashSet<&str>, removed: &HashSet<(&str, i32)>) -> PathMap {
+    let allowed = |city: &str, edge: &Edge| {
+        !blocked.contains(edge.to.as_str()) && !removed.contains(&(city, edge.road_id))
+    };
+    search(start, Some(goal), None, graph, |_| 0, allowed).0
+}
+
+
+///
+/// Dijkstra on HashMap without early stop
+///
+/// returns: PathMap => Paths to every city reachable from `start`
+///
+pub fn dijkstra_all(start: &str, graph: &Graph) -> PathMap {
+    search(start, None, None, graph, |_| 0, |_, _| true).0
+}
+
+
+/// `dijkstra_all`, by the matrix solver if the graph is dense
+pub fn shortest_path_tree(start: &str, graph: &Graph, dense: Option<&DenseGraph>) -> PathMap {
+    match dense {
+        Some(dense) => dense.paths(start),
+        None => dijkstra_all(start, graph),
+    }
+}
+
+
+///
+/// Dijkstra on HashMap that never goes further than `max_cost`
+///
+/// returns: PathMap => Paths to every city within `max_cost` from `start`
+///
+pub fn dijkstra_bounded(start: &str, max_cost: i32, graph: &Graph) -> PathMap {
+    search(start, None, Some(max_cost), graph, |_| 0, |_, _| true).0
+}
+
+
+///
+/// Dijkstra from `start` that stops once `k` facilities are reached
+///
+/// returns: Vec<(String, i32)> => Up to `k` nearest facilities with their costs, nearest first
+///
+pub fn nearest_facilities(start: &str, facilities: &HashSet<String>, k: usize, graph: &Graph) -> Vec<(String, i32)> {
+    let mut pq: PriorityQueue<String, Reverse<i32>> = PriorityQueue::new();
+    pq.push(start.to_string(), Reverse(0));
+    let mut costs: HashMap<String, i32> = HashMap::new();
+    costs.insert(start.to_string(), 0);
+
+    let mut found = Vec::new();
+    while let Some((current_node, Reverse(current_cost))) = pq.pop() {
+        if found.len() == k { break };
+        if facilities.contains(&current_node) {
+            found.push((current_node.clone(), current_cost));
+        }
+        for edge in &graph[&current_node] {
+            let new_cost = current_cost.saturating_add(edge.weight);
+            if costs.get(&edge.to).is_none_or(|&cost| new_cost < cost) {
+                costs.insert(edge.to.clone(), new_cost);
+                pq.push(edge.to.clone(), Reverse(new_cost));
             }
         }
-        visited[curent as usize] = true;
-        for (index, lenth) in dist.iter().enumerate() {
-            result[index] = min(result[index], result[curent as usize] + lenth);
+    }
+    found
+}
+
+
+///
+/// # Multi-source Dijkstra
+/// Searches from all `sources` at once, every city is claimed by the source that reaches it first.
+/// Run it on `reverse_graph` to get costs from the cities to the sources.
+///
+/// returns: HashMap<String, (String, i32)> => Reachable city -> (nearest source, cost)
+///
+pub fn multi_source_dijkstra(sources: &[String], graph: &Graph) -> HashMap<String, (String, i32)> {
+    let mut pq: PriorityQueue<String, Reverse<(i32, String)>> = PriorityQueue::new();
+    let mut nearest: HashMap<String, (String, i32)> = HashMap::new();
+    for source in sources {
+        nearest.insert(source.clone(), (source.clone(), 0));
+        pq.push(source.clone(), Reverse((0, source.clone())));
+    }
+
+    // Ties go to the source first by name, so the partition doesn't depend on hash order
+    while let Some((current_node, Reverse((current_cost, source)))) = pq.pop() {
+        for edge in &graph[&current_node] {
+            let label = (current_cost.saturating_add(edge.weight), source.clone());
+            if nearest.get(&edge.to).is_none_or(|(old_source, old_cost)| label < (*old_cost, old_source.clone())) {
+                nearest.insert(edge.to.clone(), (source.clone(), label.0));
+                pq.push(edge.to.clone(), Reverse(label));
+            }
         }
     }
-    result
+    nearest
 }
 
-// Работа с структурами данных в Rust - это боль :(
-// ///
-// /// # Dijkstra algorithm for sparse graphs
-// /// O(M*log(N))
-// /// # Arguments
-// ///
-// /// * `n`<i32>: count of all nodes
-// /// * `start`<i32>: starting node
-// /// * `dist`<Vec<i32>>: adjacency list with Pairs (Connected node, distance to node)
-// ///
-// /// returns: Vec<i32> => Path to every node from 'start'
-// ///
-// pub fn dijkstra_path(n: usize, start: usize, dist: Vec<i32>) -> Vec<i32> {
-//     let mut dijkstra: Vec<i32> = vec![INF; n];
-//     let mut path: Vec<i32> = vec![-1; n];
-//     dijkstra[start] = 0;
-//
-//     let mut pq: PriorityQueue<i32, Reverse<i32>> = PriorityQueue::new();
-//     pq.push(start as i32, Reverse(0));
-//     while !pq.is_empty() {
-//         let (current_node, current_distance) = pq.pop().unwrap();
-//         if (current_distance > dijkstra[current_node]) { continue; }
-//         for (index, lenth) in dist.iter().enumerate() {
-//             if dijkstra[index] > dijkstra[current_node] + lenth {
-//                 dijkstra[index] = dijkstra[current_node] + lenth;
-//                 path[index] = current_node;
-//                 pq.push(index as i32, Reverse(dijkstra[index]));
-//             }
-//         }
-//     }
-//     path
-// }
+
+// (cities that can't reach any open facility, weighted total distance to the nearest one), lower is better
+pub type PlacementCost = (usize, i64);
+
+// Rounds of swaps tried after the greedy placement, each costs about as much as the greedy phase
+const PLACEMENT_SWAP_PASSES: usize = 10;
+
+// Whether distance `a` is shorter than `b`, None being unreachable
+fn closer(a: Option<i32>, b: Option<i32>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => a < b,
+        (Some(_), None) => true,
+        (None, _) => false,
+    }
+}
+
+// Nearest and second nearest open facility of every city, the nearest with the chosen candidate it is, if any
+struct NearestFacilities {
+    first: Vec<(Option<i32>, Option<usize>)>,
+    second: Vec<Option<i32>>,
+}
+
+impl NearestFacilities {
+    fn new(candidates: &[Vec<Option<i32>>], fixed: &[Vec<Option<i32>>], chosen: &[usize], cities: usize) -> Self {
+        let mut nearest = NearestFacilities { first: vec![(None, None); cities], second: vec![None; cities] };
+        for row in fixed {
+            nearest.open(row, None);
+        }
+        for &candidate in chosen {
+            nearest.open(&candidates[candidate], Some(candidate));
+        }
+        nearest
+    }
+
+    fn open(&mut self, distances: &[Option<i32>], candidate: Option<usize>) {
+        for (city, &distance) in distances.iter().enumerate() {
+            if closer(distance, self.first[city].0) {
+                self.second[city] = self.first[city].0;
+                self.first[city] = (distance, candidate);
+            } else if closer(distance, self.second[city]) {
+                self.second[city] = distance;
+            }
+        }
+    }
+
+    // Cost once `opened` is open and `closed`, a chosen candidate, isn't
+    fn cost(&self, weights: &[i64], opened: &[Option<i32>], closed: Option<usize>) -> PlacementCost {
+        let mut cost = (0, 0);
+        for (city, &weight) in weights.iter().enumerate() {
+            if weight == 0 { continue };
+            let (first, from) = self.first[city];
+            let kept = if closed.is_some() && from == closed { self.second[city] } else { first };
+            let distance = if closer(opened[city], kept) { opened[city] } else { kept };
+            match distance {
+                Some(distance) => cost.1 += weight * distance as i64,
+                None => cost.0 += 1,
+            }
+        }
+        cost
+    }
+}
 
 
 ///
-/// Dijkstra on HashMap
-pub fn dijkstra(start: String, goal: String, graph: HashMap<String, Vec<(i32, String)>>) -> HashMap<String, (Option<String>, i32)> {
+/// # k-median facility placement
+/// Greedily opens the candidate that lowers the cost most, `k` times, then for every open candidate takes the swap
+/// with a closed one that lowers the cost most, for up to `PLACEMENT_SWAP_PASSES` rounds.
+/// The nearest and second nearest open facility of every city are kept, so trying a candidate costs one pass over the cities.
+/// # Arguments
+///
+/// * `candidates`<&[Vec<Option<i32>>]>: [candidate][city] => distance from the city to the candidate, None if unreachable
+/// * `fixed`<&[Vec<Option<i32>>]>: the same for facilities that stay open anyway
+/// * `weights`<&[i64]>: [city] => weight of its distance, cities weighing 0 are left out
+/// * `k`<usize>: count of candidates to open
+///
+/// returns: (Vec<usize>, PlacementCost) => Indices of the opened candidates and the cost of the placement
+///
+pub fn k_median(candidates: &[Vec<Option<i32>>], fixed: &[Vec<Option<i32>>], weights: &[i64], k: usize)
+    -> (Vec<usize>, PlacementCost) {
+    let mut chosen: Vec<usize> = Vec::new();
+    let mut nearest = NearestFacilities::new(candidates, fixed, &chosen, weights.len());
+    for _ in 0..k.min(candidates.len()) {
+        let best = (0..candidates.len())
+            .filter(|candidate| !chosen.contains(candidate))
+            .min_by_key(|&candidate| nearest.cost(weights, &candidates[candidate], None))
+            .unwrap();
+        nearest.open(&candidates[best], Some(best));
+        chosen.push(best);
+    }
+
+    let unreachable = vec![None; weights.len()];
+    let mut cost = nearest.cost(weights, &unreachable, None);
+    for _ in 0..PLACEMENT_SWAP_PASSES {
+        let mut improved = false;
+        for i in 0..chosen.len() {
+            let swap = (0..candidates.len())
+                .filter(|candidate| !chosen.contains(candidate))
+                .map(|candidate| (nearest.cost(weights, &candidates[candidate], Some(chosen[i])), candidate))
+                .min();
+            if let Some((swap_cost, candidate)) = swap.filter(|&(swap_cost, _)| swap_cost < cost) {
+                chosen[i] = candidate;
+                cost = swap_cost;
+                nearest = NearestFacilities::new(candidates, fixed, &chosen, weights.len());
+                improved = true;
+            }
+        }
+        if !improved { break };
+    }
+    (chosen, cost)
+}
+
+
+/// Graph with every edge turned around
+pub fn reverse_graph(graph: &Graph) -> Graph {
+    let mut reversed: Graph = graph.keys().map(|name| (name.clone(), Vec::new())).collect();
+    for (from, edges) in graph {
+        for edge in edges {
+            reversed.get_mut(&edge.to).unwrap().push(Edge { to: from.clone(), ..edge.clone() });
+        }
+    }
+    reversed
+}
+
+
+///
+/// # A* search on HashMap
+/// # Arguments
+///
+/// * `heuristic`<Fn(&str) -> i32>: lower bound of the remaining cost from a city to `goal`
+///
+/// returns: (PathMap, usize) => Paths found and count of expanded nodes
+///
+pub fn astar<H: Fn(&str) -> i32>(start: &str, goal: &str, graph: &Graph, heuristic: H) -> (PathMap, usize) {
+    search(start, Some(goal), None, graph, heuristic, |_, _| true)
+}
+
+
+// Best-first search behind the Dijkstra and A* variants, only following edges `allowed` from their city
+fn search<H, A>(start: &str, goal: Option<&str>, budget: Option<i32>, graph: &Graph, heuristic: H, allowed: A)
+    -> (PathMap, usize)
+    where H: Fn(&str) -> i32, A: Fn(&str, &Edge) -> bool {
     let mut pq: PriorityQueue<String, Reverse<i32>> = PriorityQueue::new();
-    pq.push(start.clone(), Reverse(0));
+    pq.push(start.to_string(), Reverse(heuristic(start)));
 
-    let mut cost: HashMap<String, i32> = HashMap::new();
-    let mut path: HashMap<String, (Option<String>, i32)> = HashMap::new();
-    cost.insert(start.clone(), 0);
-    path.insert(start.clone(), (None, 0));
+    let mut path: PathMap = HashMap::new();
+    path.insert(start.to_string(), (None, 0));
+    let mut expanded = 0;
 
-    while !pq.is_empty() {
-        let (current_node, _) = pq.pop().unwrap();
-        if current_node == goal { break };
+    while let Some((current_node, _)) = pq.pop() {
+        expanded += 1;
+        if Some(current_node.as_str()) == goal { break };
+        let current_cost = path[&current_node].1;
 
-        for (next_cost, next_node) in graph[&current_node].clone() {
-            let new_cost = cost.get(&current_node.clone()).unwrap() + next_cost;
+        for edge in &graph[&current_node] {
+            if !allowed(&current_node, edge) { continue };
+            // Saturates rather than wrapping on absurd weights
+            let new_cost = current_cost.saturating_add(edge.weight);
+            if budget.is_some_and(|budget| new_cost > budget) { continue };
 
-            if !cost.contains_key(&next_node) || new_cost < cost[&next_node] {
-                pq.push(next_node.clone(), Reverse(new_cost));
-                cost.insert(next_node.clone(), new_cost);
-                path.insert(next_node.clone(), (Some(current_node.clone()), new_cost));
+            if !path.contains_key(&edge.to) || new_cost < path[&edge.to].1 {
+                pq.push(edge.to.clone(), Reverse(new_cost.saturating_add(heuristic(&edge.to))));
+                path.insert(edge.to.clone(), (Some((current_node.clone(), edge.clone())), new_cost));
             };
         }
     }
-    path
+    (path, expanded)
 }
 
 
-pub fn format_path(start: String, end: String, path: &HashMap<String, (Option<String>, i32)>) -> String {
-    let mut current_node = end.clone();
-    let mut reverse = Vec::new();
+///
+/// Great-circle distance between two cities in kilometres
+pub fn haversine_km(a: &City, b: &City) -> f64 {
+    const EARTH_RADIUS_KM: f64 = 6371.0;
+    let (lat_a, lat_b) = ((a.latitude as f64).to_radians(), (b.latitude as f64).to_radians());
+    let d_lat = lat_b - lat_a;
+    let d_lon = (b.longitude as f64 - a.longitude as f64).to_radians();
+
+    let h = (d_lat / 2.0).sin().powi(2) + lat_a.cos() * lat_b.cos() * (d_lon / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_KM * h.sqrt().asin()
+}
+
+
+///
+/// A* heuristic: straight-line distance to `goal` expressed in edge weight units.
+/// Stays admissible as long as no edge weighs less than `cost_per_km` times the straight line between its ends.
+/// # Arguments
+///
+/// * `cost_per_km`<f64>: lower bound of the weight of one kilometre
+///
+pub fn great_circle_heuristic<'a>(cities: &'a HashMap<String, City>, goal: &'a City, cost_per_km: f64)
+    -> impl Fn(&str) -> i32 + 'a {
+    move |name| match cities.get(name) {
+        Some(city) => (haversine_km(city, goal) * cost_per_km).floor() as i32,
+        None => 0,
+    }
+}
+
+
+///
+/// Lowest weight of one kilometre over all edges, the `cost_per_km` of an admissible heuristic
+pub fn min_cost_per_km(graph: &Graph, length_unit_km: f64) -> f64 {
+    let rate = graph.values()
+        .flatten()
+        .filter(|edge| edge.length > 0)
+        .map(|edge| edge.weight as f64 / (edge.length as f64 * length_unit_km))
+        .fold(f64::INFINITY, f64::min);
+    if rate.is_finite() { rate.max(0.0) } else { 0.0 }
+}
+
+
+///
+/// Restore the roads taken from `start` to `end`
+///
+/// returns: Vec<(String, Edge)> => (city, road leaving it) for every leg, None if `end` wasn't reached
+///
+pub fn trace_path(start: &str, end: &str, path: &PathMap) -> Option<Vec<(String, Edge)>> {
+    let mut current_node = end.to_string();
+    let mut legs = Vec::new();
     while current_node != start {
-        reverse.push(current_node.clone());
-        current_node = path.get(&current_node.clone()).unwrap().0.clone().unwrap();
+        let (prev, edge) = path.get(&current_node)?.0.clone()?;
+        current_node = prev.clone();
+        legs.push((prev, edge));
+    }
+    legs.reverse();
+    Some(legs)
+}
+
+
+///
+/// # Dijkstra/A* over (city, arrival mode) states
+/// Adds `transfer_penalty` to the cost every time the route changes between road and rail.
+/// # Arguments
+///
+/// * `start_mode`: mode the route arrived at `start` by when it continues from a waypoint, so a change there is charged too
+///
+/// returns: (Option<Vec<(String, Edge)>>, usize) => Legs in the `trace_path` format and count of expanded states
+///
+pub fn multimodal_search<H: Fn(&str) -> i32>(start: &str, goal: &str, start_mode: Option<Mode>, graph: &Graph,
+                                             transfer_penalty: i32, heuristic: H)
+    -> (Option<Vec<(String, Edge)>>, usize) {
+    type State = (String, Option<Mode>);
+    let mut pq: PriorityQueue<State, Reverse<i32>> = PriorityQueue::new();
+    let initial: State = (start.to_string(), start_mode);
+    pq.push(initial.clone(), Reverse(heuristic(start)));
+
+    let mut visits: HashMap<State, (Option<(State, Edge)>, i32)> = HashMap::new();
+    visits.insert(initial, (None, 0));
+    let mut expanded = 0;
+
+    while let Some((state, _)) = pq.pop() {
+        expanded += 1;
+        if state.0 == goal {
+            let mut legs = Vec::new();
+            let mut current = state;
+            while let Some((prev, edge)) = visits[&current].0.clone() {
+                legs.push((prev.0.clone(), edge));
+                current = prev;
+            }
+            legs.reverse();
+            return (Some(legs), expanded);
+        }
+        let current_cost = visits[&state].1;
+
+        for edge in &graph[&state.0] {
+            let transfer = match state.1 {
+                Some(mode) if mode != edge.mode => transfer_penalty,
+                _ => 0,
+            };
+            let new_cost = current_cost.saturating_add(transfer).saturating_add(edge.weight);
+            let next: State = (edge.to.clone(), Some(edge.mode));
+
+            if !visits.contains_key(&next) || new_cost < visits[&next].1 {
+                pq.push(next.clone(), Reverse(new_cost.saturating_add(heuristic(&edge.to))));
+                visits.insert(next, (Some((state.clone(), edge.clone())), new_cost));
+            };
+        }
+    }
+    (None, expanded)
+}
+
+
+pub fn path_length(legs: &[(String, Edge)]) -> i32 {
+    legs.iter().map(|(_, edge)| edge.length).sum()
+}
+
+
+pub fn path_duration(legs: &[(String, Edge)]) -> i32 {
+    legs.iter().map(|(_, edge)| edge.duration).sum()
+}
+
+
+pub fn path_toll(legs: &[(String, Edge)]) -> f64 {
+    legs.iter().map(|(_, edge)| edge.toll).sum()
+}
+
+
+/// Fuel burnt on the road legs plus tolls
+pub fn path_cost(legs: &[(String, Edge)], fuel: &Fuel, length_unit_km: f64) -> f64 {
+    let road_km: f64 = legs.iter()
+        .filter(|(_, edge)| edge.mode == Mode::Road)
+        .map(|(_, edge)| edge.length as f64 * length_unit_km)
+        .sum();
+    fuel.cost(road_km) + path_toll(legs)
+}
+
+
+pub fn path_weight(legs: &[(String, Edge)]) -> i32 {
+    legs.iter().fold(0, |total: i32, (_, edge)| total.saturating_add(edge.weight))
+}
+
+
+///
+/// # Yen's k shortest loopless paths
+/// # Arguments
+///
+/// * `k`<usize>: maximum count of paths to find
+///
+/// returns: Vec<Vec<(String, Edge)>> => Paths in the `trace_path` format, shortest first
+///
+pub fn k_shortest_paths(start: &str, goal: &str, graph: &Graph, k: usize) -> Vec<Vec<(String, Edge)>> {
+    let mut found: Vec<Vec<(String, Edge)>> = Vec::new();
+    let first = match trace_path(start, goal, &dijkstra(start, goal, graph).0) {
+        Some(legs) => legs,
+        None => return found,
+    };
+    found.push(first);
+    let mut candidates: Vec<Vec<(String, Edge)>> = Vec::new();
+
+    while found.len() < k {
+        let last = found.last().unwrap().clone();
+        for i in 0..last.len() {
+            let spur_node = &last[i].0;
+            let root = &last[..i];
+
+            // Forbid the roads already used to leave the spur node by paths sharing this root,
+            // and the root's cities so the new path stays loopless
+            let mut removed: HashSet<(&str, i32)> = HashSet::new();
+            for path in &found {
+                if path.len() > i && same_legs(&path[..i], root) {
+                    removed.insert((spur_node.as_str(), path[i].1.road_id));
+                }
+            }
+            let blocked: HashSet<&str> = root.iter().map(|(city, _)| city.as_str()).collect();
+
+            if let Some(spur) = trace_path(spur_node, goal, &dijkstra_restricted(spur_node, goal, graph, &blocked, &removed)) {
+                let mut candidate = root.to_vec();
+                candidate.extend(spur);
+                if !candidates.iter().chain(found.iter()).any(|path| same_legs(path, &candidate)) {
+                    candidates.push(candidate);
+                }
+            }
+        }
+
+        if candidates.is_empty() { break };
+        let (best, _) = candidates.iter().enumerate()
+            .min_by_key(|(_, path)| path_weight(path))
+            .unwrap();
+        found.push(candidates.swap_remove(best));
     }
+    found
+}
 
+
+fn same_legs(a: &[(String, Edge)], b: &[(String, Edge)]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|((city_a, edge_a), (city_b, edge_b))| {
+        city_a == city_b && edge_a.road_id == edge_b.road_id
+    })
+}
+
+
+pub fn format_path(start: &str, end: &str, legs: &[(String, Edge)]) -> String {
     let mut result = format!("Path from {start} to {end}: \n{start} ", start=start, end=end);
-    for node in reverse.iter().rev() {
-        result += &format!("---> {node} ", node=node);
+    for (_, edge) in legs {
+        result += &format!("---> {node} ", node=edge.to);
     }
     result
-}
\ No newline at end of file
+}
+
+
+///
+/// Length of a closed tour over a distance matrix
+pub fn tour_length(dist: &[Vec<i32>], order: &[usize]) -> i32 {
+    order.iter()
+        .zip(order.iter().cycle().skip(1))
+        .fold(0, |total: i32, (&a, &b)| total.saturating_add(dist[a][b]))
+}
+
+
+///
+/// # Held-Karp exact TSP
+/// O(2^N * N^2), use for small N only
+/// # Arguments
+///
+/// * `dist`<&[Vec<i32>]>: N x N distance matrix, node 0 is the depot
+///
+/// returns: Vec<usize> => Visiting order starting from the depot (return to it is implied)
+///
+pub fn held_karp(dist: &[Vec<i32>]) -> Vec<usize> {
+    let n = dist.len();
+    if n <= 2 {
+        return (0..n).collect();
+    }
+    // cost[mask][last]: shortest way from the depot through nodes of `mask` (over 1..n) ending at `last`
+    let m = n - 1;
+    let mut cost = vec![vec![INF; n]; 1 << m];
+    let mut parent = vec![vec![usize::MAX; n]; 1 << m];
+    for last in 1..n {
+        cost[1 << (last - 1)][last] = dist[0][last];
+    }
+
+    for mask in 1..(1usize << m) {
+        for last in 1..n {
+            if mask & (1 << (last - 1)) == 0 || cost[mask][last] >= INF { continue };
+            for next in 1..n {
+                if mask & (1 << (next - 1)) != 0 { continue };
+                let next_mask = mask | (1 << (next - 1));
+                let new_cost = cost[mask][last].saturating_add(dist[last][next]);
+                if new_cost < cost[next_mask][next] {
+                    cost[next_mask][next] = new_cost;
+                    parent[next_mask][next] = last;
+                }
+            }
+        }
+    }
+
+    let full = (1 << m) - 1;
+    let mut last = (1..n).min_by_key(|&last| cost[full][last].saturating_add(dist[last][0])).unwrap();
+    let mut mask = full;
+    let mut order = Vec::with_capacity(n);
+    while last != usize::MAX && last != 0 {
+        order.push(last);
+        let prev = parent[mask][last];
+        mask &= !(1 << (last - 1));
+        last = prev;
+    }
+    order.push(0);
+    order.reverse();
+    order
+}
+
+
+///
+/// # Heuristic TSP: nearest neighbour, improved by 2-opt and Or-opt moves
+/// # Arguments
+///
+/// * `dist`<&[Vec<i32>]>: N x N distance matrix, node 0 is the depot
+///
+/// returns: Vec<usize> => Visiting order starting from the depot (return to it is implied)
+///
+pub fn tsp_heuristic(dist: &[Vec<i32>]) -> Vec<usize> {
+    let n = dist.len();
+    let mut order = vec![0];
+    let mut visited = vec![false; n];
+    visited[0] = true;
+    for _ in 1..n {
+        let last = *order.last().unwrap();
+        let next = (0..n).filter(|&i| !visited[i]).min_by_key(|&i| dist[last][i]).unwrap();
+        visited[next] = true;
+        order.push(next);
+    }
+
+    // Tours are re-measured as a whole so one-way distances stay correct
+    let mut best = tour_length(dist, &order);
+    let mut improved = true;
+    while improved {
+        improved = false;
+
+        // 2-opt: reverse a section of the tour
+        for i in 1..n {
+            for j in i + 1..n {
+                order[i..=j].reverse();
+                let length = tour_length(dist, &order);
+                if length < best {
+                    best = length;
+                    improved = true;
+                } else {
+                    order[i..=j].reverse();
+                }
+            }
+        }
+
+        // Or-opt: move a chain of up to 3 cities to another place
+        for chain in 1..=3 {
+            for i in 1..n {
+                if i + chain > n { break };
+                for j in 1..=n - chain {
+                    if j == i { continue };
+                    let mut candidate = order.clone();
+                    let moved: Vec<usize> = candidate.drain(i..i + chain).collect();
+                    candidate.splice(j..j, moved);
+                    let length = tour_length(dist, &candidate);
+                    if length < best {
+                        best = length;
+                        order = candidate;
+                        improved = true;
+                    }
+                }
+            }
+        }
+    }
+    order
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Two-way roads (id, city, city, length) weighted by length
+    fn graph(roads: &[(i32, &str, &str, i32)]) -> Graph {
+        let mut graph: Graph = HashMap::new();
+        for &(road_id, a, b, length) in roads {
+            for (from, to) in [(a, b), (b, a)] {
+                let edge = Edge { road_id, mode: Mode::Road, length, duration: 0, toll: 0.0, weight: length, to: to.to_string() };
+                graph.entry(from.to_string()).or_default().push(edge);
+                graph.entry(to.to_string()).or_default();
+            }
+        }
+        graph
+    }
+
+    fn cities(start: &str, legs: &[(String, Edge)]) -> Vec<String> {
+        let mut cities = vec![start.to_string()];
+        cities.extend(legs.iter().map(|(_, edge)| edge.to.clone()));
+        cities
+    }
+
+    #[test]
+    fn yen_lists_loopless_paths_shortest_first() {
+        let graph = graph(&[(1, "A", "B", 1), (2, "A", "C", 4), (3, "B", "C", 2), (4, "B", "D", 6), (5, "C", "D", 1)]);
+        let paths = k_shortest_paths("A", "D", &graph, 10);
+        let found: Vec<(Vec<String>, i32)> = paths.iter().map(|legs| (cities("A", legs), path_weight(legs))).collect();
+        let expected = [
+            (vec!["A", "B", "C", "D"], 4),
+            (vec!["A", "C", "D"], 5),
+            (vec!["A", "B", "D"], 7),
+            (vec!["A", "C", "B", "D"], 12),
+        ];
+        assert_eq!(found.len(), expected.len());
+        for ((cities, weight), (expected_cities, expected_weight)) in found.iter().zip(expected) {
+            assert_eq!(cities, &expected_cities);
+            assert_eq!(*weight, expected_weight);
+        }
+        assert_eq!(k_shortest_paths("A", "D", &graph, 2).len(), 2);
+    }
+
+    #[test]
+    fn yen_finds_nothing_between_disconnected_cities() {
+        let graph = graph(&[(1, "A", "B", 1), (2, "C", "D", 1)]);
+        assert!(k_shortest_paths("A", "D", &graph, 3).is_empty());
+    }
+
+    #[test]
+    fn restricted_dijkstra_skips_blocked_cities_and_removed_edges() {
+        let graph = graph(&[(1, "A", "B", 1), (2, "B", "D", 1), (3, "A", "C", 2), (4, "C", "D", 2), (5, "A", "D", 9)]);
+        let blocked: HashSet<&str> = ["B"].into_iter().collect();
+        let removed: HashSet<(&str, i32)> = [("A", 3)].into_iter().collect();
+        let legs = trace_path("A", "D", &dijkstra_restricted("A", "D", &graph, &blocked, &removed)).unwrap();
+        assert_eq!(cities("A", &legs), ["A", "D"]);
+    }
+
+    // Corners of a unit square with long diagonals
+    fn square() -> Vec<Vec<i32>> {
+        vec![
+            vec![0, 1, 10, 1],
+            vec![1, 0, 1, 10],
+            vec![10, 1, 0, 1],
+            vec![1, 10, 1, 0],
+        ]
+    }
+
+    #[test]
+    fn held_karp_walks_around_the_square() {
+        let order = held_karp(&square());
+        assert_eq!(order[0], 0);
+        assert_eq!(tour_length(&square(), &order), 4);
+    }
+
+    #[test]
+    fn held_karp_follows_one_way_distances() {
+        // Going 0 -> 1 -> 2 -> 0 costs 3, the other way round 30
+        let dist = vec![vec![0, 1, 10], vec![10, 0, 1], vec![1, 10, 0]];
+        assert_eq!(held_karp(&dist), [0, 1, 2]);
+    }
+
+    #[test]
+    fn heuristic_tour_matches_held_karp_on_small_input() {
+        let dist = square();
+        assert_eq!(tour_length(&dist, &tsp_heuristic(&dist)), tour_length(&dist, &held_karp(&dist)));
+    }
+
+    // Cities 0..n on a line one apart, every one a candidate
+    fn line(n: i32) -> Vec<Vec<Option<i32>>> {
+        (0..n).map(|candidate| (0..n).map(|city| Some((candidate - city).abs())).collect()).collect()
+    }
+
+    #[test]
+    fn k_median_opens_the_middle_of_a_line() {
+        let weights = vec![1; 5];
+        assert_eq!(k_median(&line(5), &[], &weights, 1), (vec![2], (0, 6)));
+        let (mut chosen, cost) = k_median(&line(5), &[], &weights, 2);
+        chosen.sort();
+        assert_eq!(cost, (0, 3));
+        assert!(chosen == [0, 3] || chosen == [1, 3] || chosen == [1, 4]);
+    }
+
+    #[test]
+    fn k_median_accounts_for_fixed_facilities_and_weights() {
+        let candidates = line(5);
+        let fixed = vec![candidates[0].clone()];
+        assert_eq!(k_median(&candidates, &fixed, &[1; 5], 1), (vec![3], (0, 3)));
+        // Only the last city counts
+        assert_eq!(k_median(&candidates, &[], &[0, 0, 0, 0, 7], 1), (vec![4], (0, 0)));
+    }
+
+    #[test]
+    fn k_median_serves_unreachable_cities_first() {
+        // Candidate 1 is close to everyone reachable but can't reach city 2
+        let candidates = vec![vec![Some(5), Some(5), Some(5)], vec![Some(0), Some(0), None]];
+        assert_eq!(k_median(&candidates, &[], &[1, 1, 1], 1), (vec![0], (0, 15)));
+    }
+
+    #[test]
+    fn searches_saturate_instead_of_overflowing() {
+        let graph = graph(&[(1, "A", "B", i32::MAX), (2, "B", "C", i32::MAX)]);
+        let path = dijkstra("A", "C", &graph).0;
+        assert_eq!(path["C"].1, i32::MAX);
+    }
+}
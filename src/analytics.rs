@@ -0,0 +1,238 @@
+use std::cmp::min;
+use std::collections::{HashMap, HashSet};
+use crate::algorithm::{Graph, connected_components};
+use crate::models::{Bridge, City, GraphAnalysis, Road, SpanningForest, SpanningTree};
+
+
+const UNVISITED: usize = usize::MAX;
+
+///
+/// # Connected components, bridges and articulation points
+/// Tarjan's algorithm over the graph with road directions ignored, O(N + M)
+///
+/// returns: GraphAnalysis => Cities of every component, roads and cities whose closure splits a component
+///
+pub fn analyze(graph: &Graph) -> GraphAnalysis {
+    let mut names: Vec<&String> = graph.keys().collect();
+    names.sort();
+    let index: HashMap<&String, usize> = names.iter().enumerate().map(|(i, &name)| (name, i)).collect();
+
+    // Undirected adjacency by index, every road once in each direction
+    let mut adjacency: Vec<Vec<(usize, i32)>> = vec![Vec::new(); names.len()];
+    let mut seen: HashSet<(i32, usize)> = HashSet::new();
+    for (city, edges) in graph {
+        let from = index[city];
+        for edge in edges {
+            let to = index[&edge.to];
+            if seen.insert((edge.road_id, from)) {
+                adjacency[from].push((to, edge.road_id));
+            }
+            if seen.insert((edge.road_id, to)) {
+                adjacency[to].push((from, edge.road_id));
+            }
+        }
+    }
+
+    let n = names.len();
+    let mut discovered = vec![UNVISITED; n];
+    let mut low = vec![UNVISITED; n];
+    let mut timer = 0;
+    let mut bridges: Vec<Bridge> = Vec::new();
+    let mut articulation: Vec<bool> = vec![false; n];
+
+    for root in 0..n {
+        if discovered[root] != UNVISITED { continue };
+        discovered[root] = timer;
+        low[root] = timer;
+        timer += 1;
+        let mut root_children = 0;
+
+        // (city, road it was entered by, next adjacency position to look at)
+        let mut stack: Vec<(usize, Option<i32>, usize)> = vec![(root, None, 0)];
+        while let Some(top) = stack.last_mut() {
+            let (node, parent_road, position) = *top;
+            if position < adjacency[node].len() {
+                top.2 += 1;
+                let (next, road_id) = adjacency[node][position];
+                if Some(road_id) == parent_road { continue };
+
+                if discovered[next] == UNVISITED {
+                    discovered[next] = timer;
+                    low[next] = timer;
+                    timer += 1;
+                    if node == root {
+                        root_children += 1;
+                    }
+                    stack.push((next, Some(road_id), 0));
+                } else {
+                    low[node] = min(low[node], discovered[next]);
+                }
+                continue;
+            }
+
+            stack.pop();
+            if let Some(&(parent, _, _)) = stack.last() {
+                low[parent] = min(low[parent], low[node]);
+                if low[node] > discovered[parent] {
+                    bridges.push(Bridge {
+                        road_id: parent_road.unwrap(),
+                        city_a: names[parent].clone(),
+                        city_b: names[node].clone(),
+                    });
+                }
+                if parent != root && low[node] >= discovered[parent] {
+                    articulation[parent] = true;
+                }
+            }
+        }
+        if root_children > 1 {
+            articulation[root] = true;
+        }
+    }
+    bridges.sort_by_key(|bridge| bridge.road_id);
+
+    let components = connected_components(graph);
+    let mut grouped: Vec<Vec<String>> = vec![Vec::new(); components.values().max().map_or(0, |&c| c + 1)];
+    for name in &names {
+        grouped[components[*name]].push(name.to_string());
+    }
+
+    GraphAnalysis {
+        component_count: grouped.len(),
+        components: grouped,
+        bridges,
+        articulation_cities: names.iter()
+            .zip(articulation)
+            .filter(|(_, critical)| *critical)
+            .map(|(name, _)| name.to_string())
+            .collect(),
+    }
+}
+
+
+///
+/// # Kruskal's minimum spanning forest
+/// O(M*log(M)), one tree per connected component
+///
+/// returns: Result<SpanningForest, String> => Error if a road references a city missing from `cities`
+///
+pub fn minimum_spanning_forest(cities: &[City], roads: &[Road]) -> Result<SpanningForest, String> {
+    let index: HashMap<i32, usize> = cities.iter()
+        .enumerate()
+        .map(|(i, city)| (city.id.expect("REASON"), i))
+        .collect();
+    let mut parent: Vec<usize> = (0..cities.len()).collect();
+
+    let mut sorted: Vec<&Road> = roads.iter().collect();
+    sorted.sort_by_key(|road| (road.length, road.id));
+
+    let mut selected: Vec<&Road> = Vec::new();
+    let mut redundant_roads: Vec<i32> = Vec::new();
+    for road in sorted {
+        let road_id = road.id.expect("REASON");
+        let (a, b) = match (index.get(&road.city_a), index.get(&road.city_b)) {
+            (Some(&a), Some(&b)) => (a, b),
+            (None, _) => return Err(format!("road {} references unknown city {}", road_id, road.city_a)),
+            (_, None) => return Err(format!("road {} references unknown city {}", road_id, road.city_b)),
+        };
+        let (root_a, root_b) = (find(&mut parent, a), find(&mut parent, b));
+        if root_a == root_b {
+            redundant_roads.push(road_id);
+        } else {
+            parent[root_a] = root_b;
+            selected.push(road);
+        }
+    }
+
+    let mut trees: HashMap<usize, SpanningTree> = HashMap::new();
+    for (i, city) in cities.iter().enumerate() {
+        let root = find(&mut parent, i);
+        trees.entry(root)
+            .or_insert_with(|| SpanningTree { cities: Vec::new(), roads: Vec::new(), length: 0 })
+            .cities.push(city.name.clone());
+    }
+    for road in &selected {
+        let root = find(&mut parent, index[&road.city_a]);
+        let tree = trees.get_mut(&root).unwrap();
+        tree.roads.push(road.id.expect("REASON"));
+        tree.length += road.length;
+    }
+    let mut trees: Vec<SpanningTree> = trees.into_values().collect();
+    for tree in trees.iter_mut() {
+        tree.cities.sort();
+        tree.roads.sort();
+    }
+    trees.sort_by(|a, b| a.cities.cmp(&b.cities));
+
+    let mut selected_ids: Vec<i32> = selected.iter().map(|road| road.id.expect("REASON")).collect();
+    selected_ids.sort();
+    redundant_roads.sort();
+    Ok(SpanningForest {
+        total_length: selected.iter().map(|road| road.length).sum(),
+        roads: selected_ids,
+        redundant_roads,
+        trees,
+    })
+}
+
+
+// Union-find root lookup with path halving
+fn find(parent: &mut [usize], mut node: usize) -> usize {
+    while parent[node] != node {
+        parent[node] = parent[parent[node]];
+        node = parent[node];
+    }
+    node
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::Edge;
+    use crate::models::Mode;
+
+    // Two-way roads (id, city, city), every city listed so isolated ones stay in the graph
+    fn graph(cities: &[&str], roads: &[(i32, &str, &str)]) -> Graph {
+        let mut graph: Graph = cities.iter().map(|city| (city.to_string(), Vec::new())).collect();
+        for &(road_id, a, b) in roads {
+            for (from, to) in [(a, b), (b, a)] {
+                let edge = Edge { road_id, mode: Mode::Road, length: 1, duration: 0, toll: 0.0, weight: 1, to: to.to_string() };
+                graph.get_mut(from).unwrap().push(edge);
+            }
+        }
+        graph
+    }
+
+    #[test]
+    fn finds_components_bridges_and_articulation_cities() {
+        // Triangle A-B-C, then C-D-E as a chain, F on its own
+        let graph = graph(
+            &["A", "B", "C", "D", "E", "F"],
+            &[(1, "A", "B"), (2, "B", "C"), (3, "C", "A"), (4, "C", "D"), (5, "D", "E")]);
+        let analysis = analyze(&graph);
+        assert_eq!(analysis.component_count, 2);
+        assert_eq!(analysis.components, [vec!["A", "B", "C", "D", "E"], vec!["F"]]);
+        let mut bridges: Vec<i32> = analysis.bridges.iter().map(|bridge| bridge.road_id).collect();
+        bridges.sort();
+        assert_eq!(bridges, [4, 5]);
+        assert_eq!(analysis.articulation_cities, ["C", "D"]);
+    }
+
+    #[test]
+    fn cycles_have_no_bridges() {
+        let graph = graph(&["A", "B", "C", "D"], &[(1, "A", "B"), (2, "B", "C"), (3, "C", "D"), (4, "D", "A")]);
+        let analysis = analyze(&graph);
+        assert!(analysis.bridges.is_empty());
+        assert!(analysis.articulation_cities.is_empty());
+    }
+
+    #[test]
+    fn parallel_roads_are_not_bridges() {
+        let graph = graph(&["A", "B", "C"], &[(1, "A", "B"), (2, "A", "B"), (3, "B", "C")]);
+        let analysis = analyze(&graph);
+        let bridges: Vec<i32> = analysis.bridges.iter().map(|bridge| bridge.road_id).collect();
+        assert_eq!(bridges, [3]);
+        assert_eq!(analysis.articulation_cities, ["B"]);
+    }
+}
@@ -0,0 +1,209 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::time::{Duration, Instant};
+use postgres::Client;
+use crate::algorithm::{build_graph, density, DenseGraph, Graph, GraphOptions};
+use crate::db;
+use crate::indexed::{ContractionHierarchy, IndexedGraph};
+use crate::models::{City, Closure, Railway, Road};
+
+
+/// Snapshot of the cities, roads, railways and closures tables, shared by concurrent requests
+pub struct Network {
+    // Bumped by every write to the tables
+    pub version: u64,
+    pub nodes: Vec<City>,
+    pub roads: Vec<Road>,
+    pub railways: Vec<Railway>,
+    pub closures: Vec<Closure>,
+    // Cities by name
+    pub cities: HashMap<String, City>,
+    // Graph of `road_options()`, which most searches run on
+    pub road_graph: Arc<Graph>,
+    // Built from `road_graph` by the first request that needs them
+    road_indexed: OnceLock<Arc<IndexedGraph>>,
+    // Hierarchies of the last few distinct options, most recently used last
+    hierarchies: Mutex<Vec<CachedHierarchy>>,
+    road_dense: OnceLock<Option<Arc<DenseGraph>>>,
+}
+
+impl Network {
+    /// Closures in force at `at`, in seconds since the Unix epoch
+    pub fn active_closures(&self, at: i64) -> Vec<&Closure> {
+        let parse = |text: &str| db::parse_timestamp(text).unwrap_or(i64::MIN);
+        self.closures.iter()
+            .filter(|closure| parse(&closure.starts_at) <= at && at < parse(&closure.ends_at))
+            .collect()
+    }
+
+    /// Roads closed at `at`, in seconds since the Unix epoch
+    pub fn closed_roads(&self, at: i64) -> Vec<i32> {
+        let mut roads: Vec<i32> = self.active_closures(at).iter().map(|closure| closure.road_id).collect();
+        roads.sort();
+        roads.dedup();
+        roads
+    }
+
+    /// Graph of the network for `options`, built only if they differ from `road_options()`
+    pub fn graph(&self, options: &GraphOptions) -> Result<Arc<Graph>, String> {
+        if *options == road_options() {
+            return Ok(self.road_graph.clone());
+        }
+        build_graph(&self.nodes, &self.roads, &self.railways, options).map(Arc::new)
+    }
+
+    /// Indexed form of `graph`, which was built for `options`
+    pub fn indexed(&self, options: &GraphOptions, graph: &Graph) -> Arc<IndexedGraph> {
+        if *options == road_options() {
+            return self.road_indexed
+                .get_or_init(|| Arc::new(IndexedGraph::from_graph(&self.road_graph)))
+                .clone();
+        }
+        Arc::new(IndexedGraph::from_graph(graph))
+    }
+
+    ///
+    /// Contraction hierarchy of `graph`, which was built for `options`, with the indexed graph it was preprocessed on.
+    /// Preprocessed once for each of the last `HIERARCHY_CACHE_SIZE` distinct options, so repeated queries
+    /// with the same closures, avoid lists or vehicle reuse it
+    ///
+    pub fn hierarchy(&self, options: &GraphOptions, graph: &Graph) -> (Arc<IndexedGraph>, Arc<ContractionHierarchy>) {
+        {
+            let mut cached = self.hierarchies.lock().unwrap();
+            if let Some(position) = cached.iter().position(|entry| entry.options == *options) {
+                let entry = cached.remove(position);
+                let found = (entry.indexed.clone(), entry.hierarchy.clone());
+                cached.push(entry);
+                return found;
+            }
+        }
+
+        // Preprocess without holding the lock, concurrent queries on other options go on meanwhile
+        let indexed = self.indexed(options, graph);
+        let hierarchy = Arc::new(ContractionHierarchy::build(&indexed));
+        let mut cached = self.hierarchies.lock().unwrap();
+        if !cached.iter().any(|entry| entry.options == *options) {
+            if cached.len() == HIERARCHY_CACHE_SIZE {
+                cached.remove(0);
+            }
+            cached.push(CachedHierarchy { options: options.clone(), indexed: indexed.clone(), hierarchy: hierarchy.clone() });
+        }
+        (indexed, hierarchy)
+    }
+
+    /// Matrix form of `graph`, which was built for `options`, if it is denser than `dense_threshold`
+    pub fn dense(&self, options: &GraphOptions, graph: &Graph) -> Option<Arc<DenseGraph>> {
+        let build = |graph: &Graph| {
+            if density(graph) > db::routing_params().dense_threshold {
+                Some(Arc::new(DenseGraph::from_graph(graph)))
+            } else {
+                None
+            }
+        };
+        if *options == road_options() {
+            return self.road_dense.get_or_init(|| build(&self.road_graph)).clone();
+        }
+        build(graph)
+    }
+}
+
+
+// Count of distinct options whose contraction hierarchies are kept
+const HIERARCHY_CACHE_SIZE: usize = 8;
+
+struct CachedHierarchy {
+    options: GraphOptions,
+    indexed: Arc<IndexedGraph>,
+    hierarchy: Arc<ContractionHierarchy>,
+}
+
+
+// How long a snapshot is served before the database version is checked again
+const VERSION_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+struct Cache {
+    network: Option<Arc<Network>>,
+    // When the snapshot was last found to match the database version, None forces a check
+    checked_at: Option<Instant>,
+    // Bumped by `invalidate`, a snapshot read before a bump may miss the write behind it
+    generation: u64,
+}
+
+static CACHE: RwLock<Cache> = RwLock::new(Cache { network: None, checked_at: None, generation: 0 });
+
+
+/// Road network weighted by distance
+pub fn road_options() -> GraphOptions {
+    let params = db::routing_params();
+    GraphOptions::new(params.speeds.clone(), params.fuel)
+}
+
+
+///
+/// Current snapshot of the network. The database version is checked at most once a `VERSION_CHECK_INTERVAL`,
+/// and the tables are read again only if it changed
+///
+/// returns: Result<Arc<Network>, String> => Error if the tables can't be read or reference unknown cities
+///
+pub fn network(sdb: &Mutex<Client>) -> Result<Arc<Network>, String> {
+    let (cached, generation) = {
+        let cache = CACHE.read().unwrap();
+        if let (Some(network), Some(checked_at)) = (&cache.network, cache.checked_at) {
+            if checked_at.elapsed() < VERSION_CHECK_INTERVAL {
+                return Ok(network.clone());
+            }
+        }
+        (cache.network.clone(), cache.generation)
+    };
+
+    let (version, nodes, roads, railways, closures) = {
+        let db = &mut *sdb.lock().unwrap();
+        // Read before the tables: a write in between only makes the snapshot look older than it is
+        let version = db::network_version(db).map_err(|_| "Couldn't get network version")? as u64;
+        if let Some(network) = cached.filter(|network| network.version == version) {
+            let mut cache = CACHE.write().unwrap();
+            if cache.generation == generation {
+                cache.checked_at = Some(Instant::now());
+            }
+            return Ok(network);
+        }
+        let nodes = db::get_cities(db).map_err(|_| "Can`t load Cities data.")?;
+        let roads = db::get_roads(db).map_err(|_| "Couldn't get Roads data")?;
+        let railways = db::get_railways(db).map_err(|_| "Couldn't get Railways data")?;
+        let closures = db::get_closures(db).map_err(|_| "Couldn't get Closures data")?;
+        (version, nodes, roads, railways, closures)
+    };
+    let road_graph = Arc::new(build_graph(&nodes, &roads, &railways, &road_options())?);
+    let cities = nodes.iter()
+        .map(|city| (city.name.clone(), city.clone()))
+        .collect();
+    let network = Arc::new(Network {
+        version,
+        nodes,
+        roads,
+        railways,
+        closures,
+        cities,
+        road_graph,
+        road_indexed: OnceLock::new(),
+        hierarchies: Mutex::new(Vec::new()),
+        road_dense: OnceLock::new(),
+    });
+
+    // A concurrent request may have loaded a newer snapshot meanwhile, and a write invalidated since
+    // may be missing from this one, which then only serves the request that built it
+    let mut cache = CACHE.write().unwrap();
+    if cache.generation == generation && cache.network.as_ref().is_none_or(|cached| cached.version <= version) {
+        cache.network = Some(network.clone());
+        cache.checked_at = Some(Instant::now());
+    }
+    Ok(network)
+}
+
+
+/// Check the database version on the next request, so writes made through the API are seen at once
+pub fn invalidate() {
+    let mut cache = CACHE.write().unwrap();
+    cache.checked_at = None;
+    cache.generation += 1;
+}
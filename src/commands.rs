@@ -1,7 +1,12 @@
 use std::sync::{Arc, Mutex};
+use std::time::Instant;
 use iron::*;
 use postgres::Client;
 use crate::{db, handlers};
+use crate::algorithm::{build_graph, dijkstra, path_weight, trace_path, Edge};
+use crate::cache::road_options;
+use crate::indexed::{ContractionHierarchy, IndexedGraph};
+use crate::models::{City, Direction, Road};
 
 
 // Macros
@@ -46,12 +51,40 @@ pub fn serve(db: Client) {
         [get, "/api/v1/cities/:name", handlers::get_city, "get_city"],
         [post, "/api/v1/cities", handlers::add_city, "add_city"],
         [delete, "/api/v1/cities/:id", handlers::delete_city, "delete_city"],
+        [post, "/api/v1/cities/:id/facility", handlers::add_facility, "add_facility"],
+        [delete, "/api/v1/cities/:id/facility", handlers::remove_facility, "remove_facility"],
         // Roads
         [get, "/api/v1/roads", handlers::get_roads, "get_roads"],
         [post, "/api/v1/roads", handlers::add_road, "add_road"],
         [delete, "/api/v1/roads/:id", handlers::delete_road, "delete_road"],
+        // Railways
+        [get, "/api/v1/railways", handlers::get_railways, "get_railways"],
+        [post, "/api/v1/railways", handlers::add_railway, "add_railway"],
+        [delete, "/api/v1/railways/:id", handlers::delete_railway, "delete_railway"],
+        // Vehicles
+        [get, "/api/v1/vehicles", handlers::get_vehicles, "get_vehicles"],
+        [get, "/api/v1/vehicles/:id", handlers::get_vehicle, "get_vehicle"],
+        [post, "/api/v1/vehicles", handlers::add_vehicle, "add_vehicle"],
+        [delete, "/api/v1/vehicles/:id", handlers::delete_vehicle, "delete_vehicle"],
+        // Road closures
+        [get, "/api/v1/closures", handlers::get_closures, "get_closures"],
+        [get, "/api/v1/closures/active", handlers::get_active_closures, "get_active_closures"],
+        [post, "/api/v1/closures", handlers::add_closure, "add_closure"],
+        [put, "/api/v1/closures/:id", handlers::update_closure, "update_closure"],
+        [delete, "/api/v1/closures/:id", handlers::delete_closure, "delete_closure"],
         // Algorithms
-        [get, "/api/v1/path", handlers::get_shortest_path, "get_shortest_path"]
+        [get, "/api/v1/path", handlers::get_shortest_path, "get_shortest_path"],
+        [get, "/api/v1/path/alternatives", handlers::get_alternative_paths, "get_alternative_paths"],
+        [get, "/api/v1/path/tree", handlers::get_path_tree, "get_path_tree"],
+        [get, "/api/v1/facilities", handlers::get_facilities, "get_facilities"],
+        [get, "/api/v1/facilities/nearest", handlers::get_nearest_facilities, "get_nearest_facilities"],
+        [get, "/api/v1/facilities/assignment", handlers::get_facility_assignment, "get_facility_assignment"],
+        [get, "/api/v1/facilities/placement", handlers::get_facility_placement, "get_facility_placement"],
+        [post, "/api/v1/tour", handlers::get_tour, "get_tour"],
+        [get, "/api/v1/reachable", handlers::get_reachable, "get_reachable"],
+        [post, "/api/v1/matrix", handlers::get_distance_matrix, "get_distance_matrix"],
+        [get, "/api/v1/graph/analysis", handlers::get_graph_analysis, "get_graph_analysis"],
+        [get, "/api/v1/graph/mst", handlers::get_spanning_tree, "get_spanning_tree"]
     );
 
     Iron::new(router).http("0.0.0.0:3000").unwrap();
@@ -103,6 +136,142 @@ pub fn show(mut db: &mut Client, args: &Vec<String>) {
 }
 
 
+pub fn rail_add(db: &mut Client, args: &[String]) {
+    if args.len() != 5 {
+        panic!("Usage: rail-add CITY_A_ID CITY_B_ID LENGTH");
+    }
+    let city_a = args[2].parse().unwrap();
+    let city_b = args[3].parse().unwrap();
+    let length = args[4].parse().unwrap();
+    let r = db::insert_railway(db, city_a, city_b, length)
+        .unwrap();
+    println!("{} rows affected", r);
+}
+
+pub fn rail_del(db: &mut Client, args: &[String]) {
+    if args.len() < 3 {
+        panic!("Usage: rail-del ID...");
+    }
+    let ids: Vec<i32> = args[2..].iter()
+        .map(|s| s.parse().unwrap())
+        .collect();
+
+    db::remove_railways(db, &ids)
+        .unwrap();
+}
+
+pub fn rail_show(db: &mut Client, args: &[String]) {
+    if args.len() != 2 {
+        panic!("Usage: rail-show");
+    }
+    let r = db::get_railways(db).unwrap();
+    db::format_railways(&r);
+}
+
+
+pub fn bench(args: &[String]) {
+    if args.len() > 4 {
+        panic!("Usage: bench [SIZE] [QUERIES]");
+    }
+    let size: usize = args.get(2).map_or(100, |s| s.parse().unwrap());
+    let queries: usize = args.get(3).map_or(200, |s| s.parse().unwrap());
+
+    // Grid of size x size cities with random road lengths, same for every run
+    let mut seed: u64 = 42;
+    let mut random = move |bound: u64| {
+        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (seed >> 33) % bound
+    };
+    let cities: Vec<City> = (0..size * size)
+        .map(|i| City {
+            id: Some(i as i32),
+            name: format!("r{}c{}", i / size, i % size),
+            latitude: (i / size) as f32 * 0.01,
+            longitude: (i % size) as f32 * 0.01,
+            facility: false,
+            population: None,
+        })
+        .collect();
+    let mut roads = Vec::new();
+    for i in 0..size * size {
+        let neighbours = [(i % size + 1 < size, i + 1), (i / size + 1 < size, i + size)];
+        for (exists, j) in neighbours {
+            if exists {
+                roads.push(Road {
+                    id: Some(roads.len() as i32),
+                    city_a: i as i32,
+                    city_b: j as i32,
+                    length: 1 + random(100) as i32,
+                    direction: Direction::Both,
+                    road_class: None,
+                    speed_limit: None,
+                    travel_time: None,
+                    toll: 0.0,
+                    max_weight: None,
+                    max_height: None,
+                    hazmat_allowed: true,
+                });
+            }
+        }
+    }
+    let pairs: Vec<(String, String)> = (0..queries)
+        .map(|_| {
+            let (a, b) = (random(cities.len() as u64) as usize, random(cities.len() as u64) as usize);
+            (cities[a].name.clone(), cities[b].name.clone())
+        })
+        .collect();
+
+    let started = Instant::now();
+    let graph = build_graph(&cities, &roads, &[], &road_options()).unwrap();
+    let indexed = IndexedGraph::from_graph(&graph);
+    println!("Grid {size}x{size}: {} cities, {} roads, built in {:?}", cities.len(), roads.len(), started.elapsed());
+    let started = Instant::now();
+    let hierarchy = ContractionHierarchy::build(&indexed);
+    println!("Contraction hierarchy: {} shortcuts, preprocessed in {:?}", hierarchy.shortcuts, started.elapsed());
+
+    // Every algorithm must find paths of the same lengths as plain Dijkstra
+    let mut expected = Vec::new();
+    type Search<'a> = &'a dyn Fn(&str, &str) -> (Option<Vec<(String, Edge)>>, usize);
+    let runs: [(&str, Search); 3] = [
+        ("dijkstra", &|start, goal| {
+            let (path, expanded) = dijkstra(start, goal, &graph);
+            (trace_path(start, goal, &path), expanded)
+        }),
+        ("bidirectional", &|start, goal| {
+            let (edges, expanded) = indexed.bidirectional_dijkstra(indexed.index[start], indexed.index[goal]);
+            (edges.map(|edges| indexed.legs(&edges)), expanded)
+        }),
+        ("ch", &|start, goal| {
+            let (edges, expanded) = hierarchy.query(indexed.index[start], indexed.index[goal]);
+            (edges.map(|edges| indexed.legs(&edges)), expanded)
+        }),
+    ];
+    println!("{:<15}{:>15}{:>15}{:>15}", "algorithm", "total", "per query", "expanded");
+    for (name, run) in runs {
+        let started = Instant::now();
+        let mut expanded = 0;
+        let mut lengths = Vec::new();
+        for (start, goal) in &pairs {
+            let (legs, count) = run(start, goal);
+            expanded += count;
+            lengths.push(legs.map(|legs| path_weight(&legs)));
+        }
+        let elapsed = started.elapsed();
+        if expected.is_empty() {
+            expected = lengths;
+        } else if lengths != expected {
+            panic!("{} found paths of other lengths than dijkstra", name);
+        }
+        println!("{:<15}{:>15}{:>15}{:>15}",
+            name,
+            format!("{:.1?}", elapsed),
+            format!("{:.1?}", elapsed / queries.max(1) as u32),
+            expanded / queries.max(1)
+        );
+    }
+}
+
+
 pub const HELP: &'static str = "Usage: phonebook COMMAND [ARG]...
 Commands:
     add NAME - create new record;
@@ -110,6 +279,10 @@ Commands:
     edit ID        - edit record;
     show           - display all records;
     show STRING    - display records which contain a given substring in the name;
+    rail-add CITY_A_ID CITY_B_ID LENGTH - create new railway;
+    rail-del ID1 ID2... - delete railways;
+    rail-show      - display all railways;
+    bench [SIZE] [QUERIES] - compare path searches on a generated SIZE x SIZE grid;
     serve          - start REST API server;
     help           - display this help.";
 
@@ -1,7 +1,11 @@
-use postgres::{Client, Error, IsolationLevel};
-use std::sync::Mutex;
-use ini::Ini;
+use postgres::{Client, Error, IsolationLevel, Row};
+use postgres::types::ToSql;
+use std::sync::{Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::str::FromStr;
+use ini::{Ini, Properties};
 use crate::models::*;
+use crate::algorithm::{Fuel, Speeds};
 
 
 pub struct ConnectParams {
@@ -13,6 +17,26 @@ pub struct ConnectParams {
 }
 
 
+pub struct RoutingParams {
+    // Kilometres in one unit of road length, used to keep the A* heuristic admissible
+    pub length_unit_km: f64,
+    // Speeds to estimate travel time from, [Speeds] section
+    pub speeds: Speeds,
+    // Default fuel price and consumption of cost estimates, [Costs] section
+    pub fuel: Fuel,
+    // Largest count of tour stops solved exactly, longer tours use a heuristic
+    pub tsp_exact_limit: usize,
+    // Largest count of tour stops accepted at all
+    pub tsp_max_stops: usize,
+    // Cost added at every change between road and rail in multimodal routes
+    pub transfer_penalty: i32,
+    // Share of directly linked city pairs above which searches use the O(N^2) matrix solver
+    pub dense_threshold: f64,
+    // Largest k x candidates x cities product facility placement works through
+    pub placement_limit: usize,
+}
+
+
 pub fn init_db(db: &mut Client) {
     db.execute(
         concat!(
@@ -33,6 +57,14 @@ pub fn init_db(db: &mut Client) {
         ),
         &[]).unwrap();
 
+    db.execute(
+        concat!(
+        r#"ALTER TABLE city "#,
+        r#"ADD COLUMN IF NOT EXISTS facility BOOLEAN NOT NULL DEFAULT FALSE, "#,
+        r#"ADD COLUMN IF NOT EXISTS population INTEGER CHECK (population >= 0);"#,
+        ),
+        &[]).unwrap();
+
     db.execute(
         concat!(
         r#"CREATE TABLE IF NOT EXISTS road ("#,
@@ -48,6 +80,157 @@ pub fn init_db(db: &mut Client) {
         r#"REFERENCES city(id)); "#,
         ),
         &[]).unwrap();
+
+    db.execute(
+        concat!(
+        r#"ALTER TABLE road "#,
+        r#"ADD COLUMN IF NOT EXISTS direction varchar(10) NOT NULL DEFAULT 'both' "#,
+        r#"CHECK (direction IN ('both', 'a_to_b', 'b_to_a'));"#,
+        ),
+        &[]).unwrap();
+
+    db.execute(
+        concat!(
+        r#"ALTER TABLE road "#,
+        r#"ADD COLUMN IF NOT EXISTS road_class varchar(20), "#,
+        r#"ADD COLUMN IF NOT EXISTS speed_limit INTEGER, "#,
+        r#"ADD COLUMN IF NOT EXISTS travel_time INTEGER;"#,
+        ),
+        &[]).unwrap();
+
+    db.execute(
+        concat!(
+        r#"ALTER TABLE road "#,
+        r#"ADD COLUMN IF NOT EXISTS toll DOUBLE PRECISION NOT NULL DEFAULT 0 CHECK (toll >= 0);"#,
+        ),
+        &[]).unwrap();
+
+    db.execute(
+        concat!(
+        r#"ALTER TABLE road "#,
+        r#"ADD COLUMN IF NOT EXISTS max_weight DOUBLE PRECISION CHECK (max_weight > 0), "#,
+        r#"ADD COLUMN IF NOT EXISTS max_height DOUBLE PRECISION CHECK (max_height > 0), "#,
+        r#"ADD COLUMN IF NOT EXISTS hazmat_allowed BOOLEAN NOT NULL DEFAULT TRUE;"#,
+        ),
+        &[]).unwrap();
+
+    // Columns added by older versions without the checks get them here, rows already stored are left as they are
+    db.batch_execute(
+        concat!(
+        r#"DO $$ BEGIN "#,
+        r#"IF NOT EXISTS (SELECT 1 FROM pg_constraint WHERE conname = 'road_speed_limit_check') THEN "#,
+        r#"ALTER TABLE road ADD CONSTRAINT road_speed_limit_check CHECK (speed_limit > 0) NOT VALID; "#,
+        r#"END IF; "#,
+        r#"IF NOT EXISTS (SELECT 1 FROM pg_constraint WHERE conname = 'road_travel_time_check') THEN "#,
+        r#"ALTER TABLE road ADD CONSTRAINT road_travel_time_check CHECK (travel_time >= 0) NOT VALID; "#,
+        r#"END IF; "#,
+        r#"IF NOT EXISTS (SELECT 1 FROM pg_constraint WHERE conname = 'road_max_weight_check') THEN "#,
+        r#"ALTER TABLE road ADD CONSTRAINT road_max_weight_check CHECK (max_weight > 0) NOT VALID; "#,
+        r#"END IF; "#,
+        r#"IF NOT EXISTS (SELECT 1 FROM pg_constraint WHERE conname = 'road_max_height_check') THEN "#,
+        r#"ALTER TABLE road ADD CONSTRAINT road_max_height_check CHECK (max_height > 0) NOT VALID; "#,
+        r#"END IF; "#,
+        r#"END $$;"#,
+        )).unwrap();
+
+    db.execute(
+        concat!(
+        r#"CREATE TABLE IF NOT EXISTS railway ("#,
+        r#"id SERIAL PRIMARY KEY, "#,
+        r#"city_a INTEGER NOT NULL, "#,
+        r#"city_b INTEGER NOT NULL, "#,
+        r#"length INTEGER NOT NULL CHECK (length > 0), "#,
+        r#"CONSTRAINT fk_city_a "#,
+        r#"FOREIGN KEY(city_a) "#,
+        r#"REFERENCES city(id), "#,
+        r#"CONSTRAINT fk_city_b "#,
+        r#"FOREIGN KEY(city_b) "#,
+        r#"REFERENCES city(id)); "#,
+        ),
+        &[]).unwrap();
+
+    // Tables created by older versions without the checks get them here
+    db.batch_execute(
+        concat!(
+        r#"DO $$ BEGIN "#,
+        r#"IF NOT EXISTS (SELECT 1 FROM pg_constraint WHERE conname = 'railway_length_check') THEN "#,
+        r#"ALTER TABLE railway ADD CONSTRAINT railway_length_check CHECK (length > 0) NOT VALID; "#,
+        r#"END IF; "#,
+        r#"IF NOT EXISTS (SELECT 1 FROM pg_constraint WHERE conname = 'railway_not_null_check') THEN "#,
+        r#"ALTER TABLE railway ADD CONSTRAINT railway_not_null_check "#,
+        r#"CHECK (city_a IS NOT NULL AND city_b IS NOT NULL AND length IS NOT NULL) NOT VALID; "#,
+        r#"END IF; "#,
+        r#"END $$;"#,
+        )).unwrap();
+
+    db.execute(
+        concat!(
+        r#"CREATE TABLE IF NOT EXISTS closure ("#,
+        r#"id SERIAL PRIMARY KEY, "#,
+        r#"road_id INTEGER NOT NULL, "#,
+        r#"starts_at TIMESTAMPTZ NOT NULL, "#,
+        r#"ends_at TIMESTAMPTZ NOT NULL, "#,
+        r#"reason TEXT NOT NULL DEFAULT '', "#,
+        r#"CHECK (ends_at > starts_at), "#,
+        r#"CONSTRAINT fk_road "#,
+        r#"FOREIGN KEY(road_id) "#,
+        r#"REFERENCES road(id) ON DELETE CASCADE); "#,
+        ),
+        &[]).unwrap();
+
+    db.execute(
+        concat!(
+        r#"CREATE TABLE IF NOT EXISTS vehicle ("#,
+        r#"id SERIAL PRIMARY KEY, "#,
+        r#"name varchar(50) UNIQUE NOT NULL, "#,
+        r#"weight DOUBLE PRECISION CHECK (weight > 0), "#,
+        r#"height DOUBLE PRECISION CHECK (height > 0), "#,
+        r#"hazmat BOOLEAN NOT NULL DEFAULT FALSE, "#,
+        r#"fuel_consumption DOUBLE PRECISION CHECK (fuel_consumption >= 0), "#,
+        r#"average_speed DOUBLE PRECISION CHECK (average_speed > 0), "#,
+        r#"modes TEXT[] NOT NULL DEFAULT '{road}'); "#,
+        ),
+        &[]).unwrap();
+
+    // Tables created by older versions without the checks get them here
+    db.batch_execute(
+        concat!(
+        r#"DO $$ BEGIN "#,
+        r#"IF NOT EXISTS (SELECT 1 FROM pg_constraint WHERE conname = 'vehicle_weight_check') THEN "#,
+        r#"ALTER TABLE vehicle ADD CONSTRAINT vehicle_weight_check CHECK (weight > 0) NOT VALID; "#,
+        r#"END IF; "#,
+        r#"IF NOT EXISTS (SELECT 1 FROM pg_constraint WHERE conname = 'vehicle_height_check') THEN "#,
+        r#"ALTER TABLE vehicle ADD CONSTRAINT vehicle_height_check CHECK (height > 0) NOT VALID; "#,
+        r#"END IF; "#,
+        r#"IF NOT EXISTS (SELECT 1 FROM pg_constraint WHERE conname = 'vehicle_fuel_consumption_check') THEN "#,
+        r#"ALTER TABLE vehicle ADD CONSTRAINT vehicle_fuel_consumption_check CHECK (fuel_consumption >= 0) NOT VALID; "#,
+        r#"END IF; "#,
+        r#"END $$;"#,
+        )).unwrap();
+
+    // Every write to the network tables bumps the version, from the API, the command line or anywhere else
+    db.batch_execute(
+        concat!(
+        r#"CREATE TABLE IF NOT EXISTS network_version ("#,
+        r#"id INTEGER PRIMARY KEY CHECK (id = 1), "#,
+        r#"version BIGINT NOT NULL); "#,
+        r#"INSERT INTO network_version VALUES (1, 1) ON CONFLICT DO NOTHING; "#,
+        r#"CREATE OR REPLACE FUNCTION bump_network_version() RETURNS trigger AS $$ "#,
+        r#"BEGIN UPDATE network_version SET version = version + 1 WHERE id = 1; RETURN NULL; END "#,
+        r#"$$ LANGUAGE plpgsql; "#,
+        r#"DROP TRIGGER IF EXISTS city_network_version ON city; "#,
+        r#"CREATE TRIGGER city_network_version AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON city "#,
+        r#"FOR EACH STATEMENT EXECUTE FUNCTION bump_network_version(); "#,
+        r#"DROP TRIGGER IF EXISTS road_network_version ON road; "#,
+        r#"CREATE TRIGGER road_network_version AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON road "#,
+        r#"FOR EACH STATEMENT EXECUTE FUNCTION bump_network_version(); "#,
+        r#"DROP TRIGGER IF EXISTS railway_network_version ON railway; "#,
+        r#"CREATE TRIGGER railway_network_version AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON railway "#,
+        r#"FOR EACH STATEMENT EXECUTE FUNCTION bump_network_version(); "#,
+        r#"DROP TRIGGER IF EXISTS closure_network_version ON closure; "#,
+        r#"CREATE TRIGGER closure_network_version AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON closure "#,
+        r#"FOR EACH STATEMENT EXECUTE FUNCTION bump_network_version();"#,
+        )).unwrap();
 }
 
 
@@ -71,6 +254,53 @@ pub fn params() -> ConnectParams {
 }
 
 
+// Read once from the optional [Routing] section of conf.ini
+pub fn routing_params() -> &'static RoutingParams {
+    static PARAMS: OnceLock<RoutingParams> = OnceLock::new();
+    PARAMS.get_or_init(|| {
+        let conf = Ini::load_from_file("conf.ini").unwrap();
+        let section = conf.section(Some("Routing"));
+        let length_unit_km = conf_value(section, "length_unit_km", 1.0);
+
+        // Every key other than `default` and `rail` is the speed of a road class
+        let speeds = conf.section(Some("Speeds"));
+        let classes = speeds.iter()
+            .flat_map(|s| s.iter())
+            .filter(|(key, _)| *key != "default" && *key != "rail")
+            .filter_map(|(key, value)| Some((key.to_string(), value.parse().ok()?)))
+            .collect();
+        let costs = conf.section(Some("Costs"));
+
+        RoutingParams {
+            length_unit_km,
+            speeds: Speeds {
+                length_unit_km,
+                default_road: conf_value(speeds, "default", 60.0),
+                rail: conf_value(speeds, "rail", 80.0),
+                classes,
+            },
+            fuel: Fuel {
+                price: conf_value(costs, "fuel_price", 1.0),
+                consumption: conf_value(costs, "fuel_consumption", 8.0),
+            },
+            tsp_exact_limit: conf_value(section, "tsp_exact_limit", 12),
+            tsp_max_stops: conf_value(section, "tsp_max_stops", 100),
+            transfer_penalty: conf_value(section, "transfer_penalty", 0),
+            dense_threshold: conf_value(section, "dense_threshold", 0.25),
+            placement_limit: conf_value(section, "placement_limit", 10_000_000),
+        }
+    })
+}
+
+
+fn conf_value<T: FromStr>(section: Option<&Properties>, key: &str, default: T) -> T {
+    section
+        .and_then(|s| s.get(key))
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+
 pub fn insert_person(db: &mut Client, name: &str, phone: &str) -> Result<u64, Error> {
     db.execute("INSERT INTO person (name, phone) VALUES ($1, $2)",
                &[&name, &phone])
@@ -175,9 +405,21 @@ pub fn read_one(sync_db: &Mutex<Client>, id: i32) -> Result<Person, ()> {
     }
 }
 
-pub fn insert_city(db: &mut Client, name: &str, latitude: f32, longitude: f32) -> Result<u64, Error> {
-    db.execute("INSERT INTO city (name, latitude, longitude) VALUES ($1, $2, $3)",
-               &[&name, &latitude, &longitude])
+pub fn insert_city(db: &mut Client, city: &City) -> Result<u64, Error> {
+    db.execute("INSERT INTO city (name, latitude, longitude, facility, population) VALUES ($1, $2, $3, $4, $5)",
+               &[&city.name, &city.latitude, &city.longitude, &city.facility, &city.population])
+}
+
+
+pub fn set_facility(db: &mut Client, id: i32, facility: bool) -> Result<u64, Error> {
+    db.execute("UPDATE city SET facility = $1 WHERE id = $2", &[&facility, &id])
+}
+
+
+// Version of the cities, roads and railways tables, bumped by triggers on every write
+pub fn network_version(db: &mut Client) -> Result<i64, Error> {
+    db.query_one("SELECT version FROM network_version WHERE id = 1", &[])
+        .map(|row| row.get("version"))
 }
 
 
@@ -198,6 +440,8 @@ pub fn get_cities(db: &mut Client) -> Result<Vec<City>, Error>{
             name: row.get("name"),
             latitude: row.get("latitude"),
             longitude: row.get("longitude"),
+            facility: row.get("facility"),
+            population: row.get("population"),
         };
         results.push(record);
     }
@@ -219,6 +463,8 @@ pub fn get_city(sync_db: &Mutex<Client>, name: Option<&str>) -> Result<City, ()>
             name: row.get("name"),
             latitude: row.get("latitude"),
             longitude: row.get("longitude"),
+            facility: row.get("facility"),
+            population: row.get("population"),
         };
 
         Ok(record)
@@ -242,9 +488,16 @@ pub fn remove_cities(db: &mut Client, ids: &[i32]) -> Result<(), Error>{
 }
 
 
-pub fn insert_road(db: &mut Client, city_a: i32, city_b: i32, length: i32) -> Result<u64, Error> {
-    db.execute("INSERT INTO road (city_a, city_b, length) VALUES ($1, $2, $3)",
-               &[&city_a, &city_b, &length])
+pub fn insert_road(db: &mut Client, road: &Road) -> Result<u64, Error> {
+    db.execute(
+        concat!(
+        "INSERT INTO road (city_a, city_b, length, direction, road_class, speed_limit, travel_time, toll, ",
+        "max_weight, max_height, hazmat_allowed) ",
+        "VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)",
+        ),
+        &[&road.city_a, &road.city_b, &road.length, &road.direction.as_str(),
+          &road.road_class, &road.speed_limit, &road.travel_time, &road.toll,
+          &road.max_weight, &road.max_height, &road.hazmat_allowed])
 }
 
 
@@ -260,11 +513,20 @@ pub fn get_roads(db: &mut Client) -> Result<Vec<Road>, Error>{
     let size = rows.iter().count();
     let mut results = Vec::with_capacity(size);
     for row in rows{
+        let direction: String = row.get("direction");
         let record = Road {
             id: row.get("id"),
             city_a: row.get("city_a"),
             city_b: row.get("city_b"),
             length: row.get("length"),
+            direction: Direction::parse(&direction).unwrap_or_default(),
+            road_class: row.get("road_class"),
+            speed_limit: row.get("speed_limit"),
+            travel_time: row.get("travel_time"),
+            toll: row.get("toll"),
+            max_weight: row.get("max_weight"),
+            max_height: row.get("max_height"),
+            hazmat_allowed: row.get("hazmat_allowed"),
         };
         results.push(record);
     }
@@ -284,3 +546,298 @@ pub fn remove_roads(db: &mut Client, ids: &[i32]) -> Result<(), Error>{
 
     transaction.commit()
 }
+
+
+pub fn insert_railway(db: &mut Client, city_a: i32, city_b: i32, length: i32) -> Result<u64, Error> {
+    db.execute("INSERT INTO railway (city_a, city_b, length) VALUES ($1, $2, $3)",
+               &[&city_a, &city_b, &length])
+}
+
+
+pub fn get_railways(db: &mut Client) -> Result<Vec<Railway>, Error>{
+    let mut transaction = db.build_transaction()
+        .isolation_level(IsolationLevel::RepeatableRead)
+        .start()?;
+
+    let stmt = transaction.prepare("SELECT * FROM railway ORDER BY id")?;
+    let rows = transaction.query(&stmt, &[])?;
+    transaction.commit()?;
+
+    let mut results = Vec::with_capacity(rows.len());
+    for row in rows{
+        let record = Railway {
+            id: row.get("id"),
+            // Rows stored before the columns were NOT NULL fail here instead of panicking
+            city_a: row.try_get("city_a")?,
+            city_b: row.try_get("city_b")?,
+            length: row.try_get("length")?,
+        };
+        results.push(record);
+    }
+    Ok(results)
+}
+
+
+pub fn remove_railways(db: &mut Client, ids: &[i32]) -> Result<(), Error>{
+    let mut transaction = db.build_transaction()
+        .isolation_level(IsolationLevel::ReadCommitted)
+        .start()?;
+
+    let stmt = transaction.prepare("DELETE FROM railway WHERE id = $1")?;
+    for id in ids{
+        transaction.execute(&stmt, &[&id])?;
+    }
+
+    transaction.commit()
+}
+
+
+pub fn format_railways(records: &[Railway]) {
+    for v in records {
+        println!("{:3?}    {:5} <-> {:5}    {}", v.id, v.city_a, v.city_b, v.length);
+    }
+}
+
+
+pub fn insert_closure(db: &mut Client, closure: &Closure) -> Result<u64, Error> {
+    db.execute(
+        concat!(
+        "INSERT INTO closure (road_id, starts_at, ends_at, reason) ",
+        "VALUES ($1, $2::text::timestamptz, $3::text::timestamptz, $4)",
+        ),
+        &[&closure.road_id, &closure.starts_at, &closure.ends_at, &closure.reason])
+}
+
+
+pub fn update_closure(db: &mut Client, id: i32, closure: &Closure) -> Result<u64, Error> {
+    db.execute(
+        concat!(
+        "UPDATE closure SET road_id = $1, starts_at = $2::text::timestamptz, ",
+        "ends_at = $3::text::timestamptz, reason = $4 WHERE id = $5",
+        ),
+        &[&closure.road_id, &closure.starts_at, &closure.ends_at, &closure.reason, &id])
+}
+
+
+pub fn get_closures(db: &mut Client) -> Result<Vec<Closure>, Error> {
+    query_closures(db, "", &[])
+}
+
+
+// Seconds since the Unix epoch of an RFC 3339 timestamp such as 2024-05-01T08:00:00+03:00,
+// None if the text isn't one
+pub fn parse_timestamp(text: &str) -> Option<i64> {
+    let bytes = text.as_bytes();
+    let layout_ok = bytes.len() >= 20 && bytes[..19].iter().enumerate().all(|(i, b)| match i {
+        4 | 7 => *b == b'-',
+        10 => matches!(b, b'T' | b't' | b' '),
+        13 | 16 => *b == b':',
+        _ => b.is_ascii_digit(),
+    });
+    if !layout_ok {
+        return None;
+    }
+    let number = |digits: &str| digits.parse::<i64>().unwrap();
+    let (year, month, day) = (number(&text[0..4]), number(&text[5..7]), number(&text[8..10]));
+    let (hour, minute, second) = (number(&text[11..13]), number(&text[14..16]), number(&text[17..19]));
+
+    let mut rest = &text[19..];
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let digits = fraction.bytes().take_while(u8::is_ascii_digit).count();
+        if digits == 0 {
+            return None;
+        }
+        rest = &fraction[digits..];
+    }
+    let offset = match rest {
+        "Z" | "z" => 0,
+        _ => {
+            let zone = rest.as_bytes();
+            if zone.len() != 6 || !matches!(zone[0], b'+' | b'-') || zone[3] != b':'
+                || ![1, 2, 4, 5].iter().all(|&i| zone[i].is_ascii_digit()) {
+                return None;
+            }
+            let (hours, minutes) = (number(&rest[1..3]), number(&rest[4..6]));
+            if hours > 23 || minutes > 59 {
+                return None;
+            }
+            let sign = if zone[0] == b'-' { -1 } else { 1 };
+            sign * (hours * 3600 + minutes * 60)
+        }
+    };
+
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let month_days = [31, if leap { 29 } else { 28 }, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+    if !(1..=12).contains(&month) || day < 1 || day > month_days[month as usize - 1]
+        || hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+
+    // Days since 1970-01-01 in the proleptic Gregorian calendar, counting years from March
+    let (y, m) = if month <= 2 { (year - 1, month + 9) } else { (year, month - 3) };
+    let era = y.div_euclid(400);
+    let year_of_era = y - era * 400;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + (153 * m + 2) / 5 + day - 1;
+    let days = era * 146_097 + day_of_era - 719_468;
+    Some(days * 86_400 + hour * 3600 + minute * 60 + second - offset)
+}
+
+
+// Seconds since the Unix epoch
+pub fn now_timestamp() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs() as i64)
+}
+
+
+fn query_closures(db: &mut Client, condition: &str, params: &[&(dyn ToSql + Sync)]) -> Result<Vec<Closure>, Error> {
+    let mut transaction = db.build_transaction()
+        .isolation_level(IsolationLevel::RepeatableRead)
+        .start()?;
+
+    let query = format!(
+        concat!(
+        "SELECT id, road_id, reason, ",
+        r#"to_char(starts_at AT TIME ZONE 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') AS starts_at, "#,
+        r#"to_char(ends_at AT TIME ZONE 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') AS ends_at "#,
+        "FROM closure {}ORDER BY id",
+        ),
+        condition);
+    let stmt = transaction.prepare(&query)?;
+    let rows = transaction.query(&stmt, params)?;
+    transaction.commit()?;
+
+    let mut results = Vec::with_capacity(rows.len());
+    for row in rows {
+        let record = Closure {
+            id: row.get("id"),
+            road_id: row.get("road_id"),
+            starts_at: row.get("starts_at"),
+            ends_at: row.get("ends_at"),
+            reason: row.get("reason"),
+        };
+        results.push(record);
+    }
+    Ok(results)
+}
+
+
+pub fn remove_closures(db: &mut Client, ids: &[i32]) -> Result<(), Error>{
+    let mut transaction = db.build_transaction()
+        .isolation_level(IsolationLevel::ReadCommitted)
+        .start()?;
+
+    let stmt = transaction.prepare("DELETE FROM closure WHERE id = $1")?;
+    for id in ids{
+        transaction.execute(&stmt, &[&id])?;
+    }
+
+    transaction.commit()
+}
+
+
+pub fn insert_vehicle(db: &mut Client, vehicle: &Vehicle) -> Result<u64, Error> {
+    let modes: Vec<&str> = vehicle.modes.iter().map(|mode| mode.as_str()).collect();
+    db.execute(
+        concat!(
+        "INSERT INTO vehicle (name, weight, height, hazmat, fuel_consumption, average_speed, modes) ",
+        "VALUES ($1, $2, $3, $4, $5, $6, $7)",
+        ),
+        &[&vehicle.name, &vehicle.weight, &vehicle.height, &vehicle.hazmat,
+          &vehicle.fuel_consumption, &vehicle.average_speed, &modes])
+}
+
+
+pub fn get_vehicles(db: &mut Client) -> Result<Vec<Vehicle>, Error>{
+    let mut transaction = db.build_transaction()
+        .isolation_level(IsolationLevel::RepeatableRead)
+        .start()?;
+
+    let stmt = transaction.prepare("SELECT * FROM vehicle ORDER BY id")?;
+    let rows = transaction.query(&stmt, &[])?;
+    transaction.commit()?;
+
+    Ok(rows.iter().map(vehicle_from_row).collect())
+}
+
+
+pub fn get_vehicle(db: &mut Client, id: i32) -> Result<Option<Vehicle>, Error> {
+    let row = db.query_opt("SELECT * FROM vehicle WHERE id = $1", &[&id])?;
+    Ok(row.as_ref().map(vehicle_from_row))
+}
+
+
+fn vehicle_from_row(row: &Row) -> Vehicle {
+    let modes: Vec<String> = row.get("modes");
+    Vehicle {
+        id: row.get("id"),
+        name: row.get("name"),
+        weight: row.get("weight"),
+        height: row.get("height"),
+        hazmat: row.get("hazmat"),
+        fuel_consumption: row.get("fuel_consumption"),
+        average_speed: row.get("average_speed"),
+        modes: modes.iter().filter_map(|mode| Mode::parse(mode)).collect(),
+    }
+}
+
+
+pub fn remove_vehicles(db: &mut Client, ids: &[i32]) -> Result<(), Error>{
+    let mut transaction = db.build_transaction()
+        .isolation_level(IsolationLevel::ReadCommitted)
+        .start()?;
+
+    let stmt = transaction.prepare("DELETE FROM vehicle WHERE id = $1")?;
+    for id in ids{
+        transaction.execute(&stmt, &[&id])?;
+    }
+
+    transaction.commit()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::parse_timestamp;
+
+    #[test]
+    fn timestamps_count_seconds_from_the_epoch() {
+        assert_eq!(parse_timestamp("1970-01-01T00:00:00Z"), Some(0));
+        assert_eq!(parse_timestamp("1969-12-31T23:59:59Z"), Some(-1));
+        assert_eq!(parse_timestamp("2000-02-29T12:00:00Z"), Some(951_825_600));
+        assert_eq!(parse_timestamp("2024-05-01T08:30:15Z"), Some(1_714_552_215));
+    }
+
+    #[test]
+    fn timestamps_honour_offsets_and_variants() {
+        let utc = parse_timestamp("2024-05-01T05:00:00Z");
+        assert_eq!(parse_timestamp("2024-05-01T08:00:00+03:00"), utc);
+        assert_eq!(parse_timestamp("2024-04-30T21:30:00-07:30"), utc);
+        assert_eq!(parse_timestamp("2024-05-01t05:00:00z"), utc);
+        assert_eq!(parse_timestamp("2024-05-01 05:00:00Z"), utc);
+        assert_eq!(parse_timestamp("2024-05-01T05:00:00.123456Z"), utc);
+        assert_eq!(parse_timestamp("2024-05-01T05:00:00+00:00"), utc);
+    }
+
+    #[test]
+    fn timestamps_know_leap_years() {
+        assert!(parse_timestamp("2024-02-29T00:00:00Z").is_some());
+        assert!(parse_timestamp("2400-02-29T00:00:00Z").is_some());
+        assert!(parse_timestamp("2023-02-29T00:00:00Z").is_none());
+        assert!(parse_timestamp("2100-02-29T00:00:00Z").is_none());
+        assert_eq!(
+            parse_timestamp("2024-03-01T00:00:00Z").unwrap() - parse_timestamp("2024-02-28T00:00:00Z").unwrap(),
+            2 * 86_400);
+    }
+
+    #[test]
+    fn invalid_timestamps_are_rejected() {
+        for text in [
+            "", "tomorrow", "2024-05-01", "2024-05-01T05:00:00", "2024-05-01T05:00Z", "2024-5-01T05:00:00Z",
+            "2024-13-01T00:00:00Z", "2024-00-10T00:00:00Z", "2024-04-31T00:00:00Z", "2024-05-00T00:00:00Z",
+            "2024-05-01T24:00:00Z", "2024-05-01T05:60:00Z", "2024-05-01T05:00:61Z", "2024-05-01T05:00:00.Z",
+            "2024-05-01T05:00:00+24:00", "2024-05-01T05:00:00+0300", "2024-05-01T05:00:00Z ", "2024-05-01X05:00:00Z",
+        ] {
+            assert_eq!(parse_timestamp(text), None, "{}", text);
+        }
+    }
+}
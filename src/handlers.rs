@@ -1,16 +1,26 @@
 use iron::*;
 use iron::mime::{Mime, TopLevel, SubLevel};
 use postgres::Client;
+use postgres::error::SqlState;
+use serde::Serialize;
 use serde_json;
 
+use std::collections::{HashMap, HashSet};
 use std::io::Read;
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 
-use crate::db;
-use crate::models::{City, Road, Person};
+use crate::{analytics, cache, db};
+use crate::cache::{road_options, Network};
+use crate::models::{City, Mode, Road, Railway, Closure, Person, Leg, Segment, PathResponse, AlternativeRoute,
+                    AlternativesResponse, TourRequest, TourResponse, ReachedCity, ReachableResponse, PathTreeResponse,
+                    CityRef, MatrixRequest, NearbyFacility, NearestFacilitiesResponse, FacilityAssignment,
+                    AssignmentResponse, PlacementResponse, MatrixResponse, CityComponent, UnreachableResponse, BlockedResponse, Vehicle};
 use crate::algorithm::*;
 
 
+const MAX_ALTERNATIVES: usize = 10;
+
+
 
 
 // Test connection
@@ -177,7 +187,8 @@ pub fn add_city(sdb: &Mutex<Client>, request: &mut Request) -> IronResult<Respon
         if record.name == "" {
             return Ok(Response::with((status::BadRequest, "empty name")));
         }
-        if let Ok(_) = db::insert_city(&mut *sdb.lock().unwrap(), &record.name, record.latitude, record.longitude) {
+        if db::insert_city(&mut sdb.lock().unwrap(), &record).is_ok() {
+            cache::invalidate();
             Ok(Response::with(status::Created))
         } else {
             Ok(Response::with((status::InternalServerError, "couldn't insert record")))
@@ -244,6 +255,7 @@ pub fn delete_city(sdb: &Mutex<Client>, request: &mut Request) -> IronResult<Res
     }
 
     if let Ok(_) = db::remove_cities(&mut *sdb.lock().unwrap(), &[id]) {
+        cache::invalidate();
         Ok(Response::with(status::NoContent))
     } else {
         Ok(Response::with((status::NotFound, "couldn't delete record")))
@@ -251,6 +263,35 @@ pub fn delete_city(sdb: &Mutex<Client>, request: &mut Request) -> IronResult<Res
 }
 
 
+// Tag city with given ID as a facility
+pub fn add_facility(sdb: &Mutex<Client>, request: &mut Request) -> IronResult<Response> {
+    set_facility(sdb, request, true)
+}
+
+// Remove the facility tag from city with given ID
+pub fn remove_facility(sdb: &Mutex<Client>, request: &mut Request) -> IronResult<Response> {
+    set_facility(sdb, request, false)
+}
+
+// Path: /api/v1/cities/:id/facility
+fn set_facility(sdb: &Mutex<Client>, request: &mut Request, facility: bool) -> IronResult<Response> {
+    let url: url::Url = request.url.clone().into();
+    let id: i32 = match url.path_segments().unwrap().nth(3).map(|id| id.parse()) {
+        Some(Ok(id)) => id,
+        _ => return Ok(Response::with((status::BadRequest, "bad id"))),
+    };
+
+    match db::set_facility(&mut sdb.lock().unwrap(), id, facility) {
+        Ok(0) => Ok(Response::with((status::NotFound, "no city with given id"))),
+        Ok(_) => {
+            cache::invalidate();
+            Ok(Response::with(status::NoContent))
+        }
+        Err(_) => Ok(Response::with((status::InternalServerError, "couldn't update record"))),
+    }
+}
+
+
 // Roads
 // Add new road from given JSON parameters
 pub fn add_road(sdb: &Mutex<Client>, request: &mut Request) -> IronResult<Response> {
@@ -258,7 +299,24 @@ pub fn add_road(sdb: &Mutex<Client>, request: &mut Request) -> IronResult<Respon
     request.body.read_to_string(&mut body).unwrap();
     let decoded: serde_json::Result<Road> = serde_json::from_str(&body);
     if let Ok(record) = decoded {
-        if let Ok(_) = db::insert_road(&mut *sdb.lock().unwrap(), record.city_a, record.city_b, record.length) {
+        if record.speed_limit.is_some_and(|speed| speed <= 0) {
+            return Ok(Response::with((status::BadRequest, "speed_limit must be positive")));
+        }
+        if !(0..=MAX_LENGTH).contains(&record.length) {
+            return Ok(Response::with((status::BadRequest, format!("length must be a number from 0 to {}", MAX_LENGTH))));
+        }
+        if record.travel_time.is_some_and(|minutes| !(0..=MAX_TRAVEL_TIME).contains(&minutes)) {
+            return Ok(Response::with((status::BadRequest,
+                                      format!("travel_time must be a number from 0 to {}", MAX_TRAVEL_TIME))));
+        }
+        if !(0.0..=MAX_TOLL).contains(&record.toll) {
+            return Ok(Response::with((status::BadRequest, format!("toll must be a number from 0 to {}", MAX_TOLL))));
+        }
+        if record.max_weight.is_some_and(|weight| weight <= 0.0) || record.max_height.is_some_and(|height| height <= 0.0) {
+            return Ok(Response::with((status::BadRequest, "max_weight and max_height must be positive")));
+        }
+        if db::insert_road(&mut sdb.lock().unwrap(), &record).is_ok() {
+            cache::invalidate();
             Ok(Response::with(status::Created))
         } else {
             Ok(Response::with((status::InternalServerError, "couldn't insert record")))
@@ -301,6 +359,7 @@ pub fn delete_road(sdb: &Mutex<Client>, request: &mut Request) -> IronResult<Res
     }
 
     if let Ok(_) = db::remove_roads(&mut *sdb.lock().unwrap(), &[id]) {
+        cache::invalidate();
         Ok(Response::with(status::NoContent))
     } else {
         Ok(Response::with((status::NotFound, "couldn't delete record")))
@@ -308,53 +367,1255 @@ pub fn delete_road(sdb: &Mutex<Client>, request: &mut Request) -> IronResult<Res
 }
 
 
-pub fn get_shortest_path(sdb: &Mutex<Client>, request: &mut Request) -> IronResult<Response> {
+// Railways
+// Add new railway from given JSON parameters
+pub fn add_railway(sdb: &Mutex<Client>, request: &mut Request) -> IronResult<Response> {
+    let mut body = String::new();
+    request.body.read_to_string(&mut body).unwrap();
+    let decoded: serde_json::Result<Railway> = serde_json::from_str(&body);
+    if let Ok(record) = decoded {
+        if !(1..=MAX_LENGTH).contains(&record.length) {
+            return Ok(Response::with((status::BadRequest, format!("length must be a number from 1 to {}", MAX_LENGTH))));
+        }
+        if db::insert_railway(&mut sdb.lock().unwrap(), record.city_a, record.city_b, record.length).is_ok() {
+            cache::invalidate();
+            Ok(Response::with(status::Created))
+        } else {
+            Ok(Response::with((status::InternalServerError, "couldn't insert record")))
+        }
+    } else {
+        Ok(Response::with((status::BadRequest, "couldn't decode JSON")))
+    }
+}
+
+// Get all railways
+pub fn get_railways(sdb: &Mutex<Client>, _request: &mut Request) -> IronResult<Response> {
+    let json_records;
+    if let Ok(records) = db::get_railways(&mut sdb.lock().unwrap()) {
+        if let Ok(json) = serde_json::to_string(&records) {
+            json_records = Some(json);
+        } else {
+            return Ok(Response::with((status::InternalServerError,
+                                      "couldn't convert records to JSON")));
+        }
+    } else {
+        return Ok(Response::with((status::InternalServerError,
+                                  "couldn't read records from database")));
+    }
+    let content_type = Mime(TopLevel::Application, SubLevel::Json, Vec::new());
+
+    Ok(Response::with((content_type, status::Ok, json_records.unwrap())))
+}
+
+
+// Delete railway with given ID
+pub fn delete_railway(sdb: &Mutex<Client>, request: &mut Request) -> IronResult<Response> {
     let url: url::Url = request.url.clone().into();
-    let mut to_city: Option<String> = None;
-    let mut from_city: Option<String> = None;
-    let qp = url.query_pairs();
-    if qp.count() != 2 {
-        return Ok(Response::with((status::BadRequest,
-                                  "passed more or less than two parameters")));
+    let mut path = url.path_segments().unwrap();
+    let sid: &str = path.next_back().unwrap();
+    let id;
+    if let Ok(r) = sid.parse() {
+        id = r;
+    } else {
+        return Ok(Response::with((status::BadRequest, "bad id")));
+    }
+
+    if db::remove_railways(&mut sdb.lock().unwrap(), &[id]).is_ok() {
+        cache::invalidate();
+        Ok(Response::with(status::NoContent))
+    } else {
+        Ok(Response::with((status::NotFound, "couldn't delete record")))
     }
-    for (k, v) in qp {
-        if k == "to" {
-            to_city = Some(v.to_string());
+}
+
+
+// Vehicles
+// Add new vehicle profile from given JSON parameters
+pub fn add_vehicle(sdb: &Mutex<Client>, request: &mut Request) -> IronResult<Response> {
+    let mut body = String::new();
+    request.body.read_to_string(&mut body).unwrap();
+    let decoded: serde_json::Result<Vehicle> = serde_json::from_str(&body);
+    if let Ok(record) = decoded {
+        if record.name.is_empty() {
+            return Ok(Response::with((status::BadRequest, "empty name")));
+        }
+        if record.modes.is_empty() {
+            return Ok(Response::with((status::BadRequest, "vehicle must allow at least one mode")));
         }
-        if k == "from" {
-            from_city = Some(v.to_string());
+        if record.weight.is_some_and(|weight| weight <= 0.0) || record.height.is_some_and(|height| height <= 0.0) {
+            return Ok(Response::with((status::BadRequest, "weight and height must be positive")));
         }
+        if record.fuel_consumption.is_some_and(|consumption| !(0.0..=MAX_FUEL_CONSUMPTION).contains(&consumption)) {
+            return Ok(Response::with((status::BadRequest,
+                                      format!("fuel_consumption must be a number from 0 to {}", MAX_FUEL_CONSUMPTION))));
+        }
+        if record.average_speed.is_some_and(|speed| speed <= 0.0) {
+            return Ok(Response::with((status::BadRequest, "average_speed must be positive")));
+        }
+        match db::insert_vehicle(&mut sdb.lock().unwrap(), &record) {
+            Ok(_) => Ok(Response::with(status::Created)),
+            Err(error) if error.code() == Some(&SqlState::UNIQUE_VIOLATION) => {
+                Ok(Response::with((status::BadRequest, format!("vehicle {} already exists", record.name))))
+            }
+            Err(_) => Ok(Response::with((status::InternalServerError, "couldn't insert record"))),
+        }
+    } else {
+        Ok(Response::with((status::BadRequest, "couldn't decode JSON")))
     }
-    if let Err(_) = db::get_city(sdb, Some(&*to_city.clone().unwrap())) {
-        return Ok(Response::with((status::BadRequest, "Can`t found start city with given parameters")));
+}
+
+
+// Get all vehicles
+pub fn get_vehicles(sdb: &Mutex<Client>, _request: &mut Request) -> IronResult<Response> {
+    match db::get_vehicles(&mut sdb.lock().unwrap()) {
+        Ok(records) => json_response(status::Ok, &records),
+        Err(_) => Ok(Response::with((status::InternalServerError, "couldn't read records from database"))),
     }
-    if let Err(_) = db::get_city(sdb, Some(&*from_city.clone().unwrap())) {
-        return Ok(Response::with((status::BadRequest, "Can`t found destination city with given parameters")));
+}
+
+
+// Get vehicle with given ID
+pub fn get_vehicle(sdb: &Mutex<Client>, request: &mut Request) -> IronResult<Response> {
+    let url: url::Url = request.url.clone().into();
+    let mut path = url.path_segments().unwrap();
+    let sid: &str = path.next_back().unwrap();
+    let id;
+    if let Ok(r) = sid.parse() {
+        id = r;
+    } else {
+        return Ok(Response::with((status::BadRequest, "bad id")));
     }
 
-    let mut json_records: Option<String> = None;
-    let edges = db::get_roads(&mut *sdb.lock().unwrap());
-    let nodes = db::get_cities(&mut *sdb.lock().unwrap());
-    if let Ok(nodes) = nodes {
-        if let Ok(edges) = edges {
-            let graph = build_graph(nodes, edges);
-            let path = dijkstra(from_city.clone().unwrap(), to_city.clone().unwrap(), graph);
+    match db::get_vehicle(&mut sdb.lock().unwrap(), id) {
+        Ok(Some(record)) => json_response(status::Ok, &record),
+        Ok(None) => Ok(Response::with((status::NotFound, "couldn't find vehicle"))),
+        Err(_) => Ok(Response::with((status::InternalServerError, "couldn't read record from database"))),
+    }
+}
+
+
+// Delete vehicle with given ID
+pub fn delete_vehicle(sdb: &Mutex<Client>, request: &mut Request) -> IronResult<Response> {
+    let url: url::Url = request.url.clone().into();
+    let mut path = url.path_segments().unwrap();
+    let sid: &str = path.next_back().unwrap();
+    let id;
+    if let Ok(r) = sid.parse() {
+        id = r;
+    } else {
+        return Ok(Response::with((status::BadRequest, "bad id")));
+    }
+
+    if db::remove_vehicles(&mut sdb.lock().unwrap(), &[id]).is_ok() {
+        Ok(Response::with(status::NoContent))
+    } else {
+        Ok(Response::with((status::NotFound, "couldn't delete record")))
+    }
+}
+
 
-            let cost: i32 = path.get(&*to_city.clone().unwrap()).unwrap().clone().1;
-            let massage = format_path(from_city.unwrap(), to_city.unwrap(), &path);
-            if let Ok(json) = serde_json::to_string(format!("{msg}\nPath length: {cost}",
-                                                            msg=massage, cost=cost).as_str()) {
-                json_records = Some(json);
+// Register a road closure
+pub fn add_closure(sdb: &Mutex<Client>, request: &mut Request) -> IronResult<Response> {
+    let mut body = String::new();
+    request.body.read_to_string(&mut body).unwrap();
+    let decoded: serde_json::Result<Closure> = serde_json::from_str(&body);
+    if let Ok(record) = decoded {
+        if let Err(response) = check_closure(sdb, &record) {
+            return Ok(response);
+        }
+        match db::insert_closure(&mut sdb.lock().unwrap(), &record) {
+            Ok(_) => {
+                cache::invalidate();
+                Ok(Response::with(status::Created))
             }
+            Err(error) => Ok(closure_write_error(&error)),
+        }
+    } else {
+        Ok(Response::with((status::BadRequest, "couldn't decode JSON")))
+    }
+}
+
+
+// Get all closures
+pub fn get_closures(sdb: &Mutex<Client>, _request: &mut Request) -> IronResult<Response> {
+    match db::get_closures(&mut sdb.lock().unwrap()) {
+        Ok(records) => json_response(status::Ok, &records),
+        Err(_) => Ok(Response::with((status::InternalServerError, "couldn't read records from database"))),
+    }
+}
+
+
+// Closures in force at a moment.
+// Query: [at=<RFC 3339 timestamp>], now by default
+pub fn get_active_closures(sdb: &Mutex<Client>, request: &mut Request) -> IronResult<Response> {
+    let params = match query_params(request) {
+        Ok(params) => params,
+        Err(response) => return Ok(response),
+    };
+    let at = match params.get("at").map(|at| db::parse_timestamp(at)) {
+        None => db::now_timestamp(),
+        Some(Some(at)) => at,
+        Some(None) => return Ok(Response::with((status::BadRequest, "at must be an RFC 3339 timestamp"))),
+    };
+    let network = match load_network(sdb) {
+        Ok(network) => network,
+        Err(response) => return Ok(response),
+    };
+    json_response(status::Ok, &network.active_closures(at))
+}
+
+
+// Update closure with given ID
+pub fn update_closure(sdb: &Mutex<Client>, request: &mut Request) -> IronResult<Response> {
+    let url: url::Url = request.url.clone().into();
+    let mut path = url.path_segments().unwrap();
+    let sid: &str = path.next_back().unwrap();
+    let id;
+    if let Ok(r) = sid.parse() {
+        id = r;
+    } else {
+        return Ok(Response::with((status::BadRequest, "bad id")));
+    }
+
+    let mut body = String::new();
+    request.body.read_to_string(&mut body).unwrap();
+    let decoded: serde_json::Result<Closure> = serde_json::from_str(&body);
+    if let Ok(record) = decoded {
+        if let Err(response) = check_closure(sdb, &record) {
+            return Ok(response);
+        }
+        match db::update_closure(&mut sdb.lock().unwrap(), id, &record) {
+            Ok(0) => Ok(Response::with((status::NotFound, "couldn't update record"))),
+            Ok(_) => {
+                cache::invalidate();
+                Ok(Response::with(status::NoContent))
+            }
+            Err(error) => Ok(closure_write_error(&error)),
+        }
+    } else {
+        Ok(Response::with((status::BadRequest, "couldn't decode JSON")))
+    }
+}
+
+
+// 400 if a closure has malformed times, an empty time window or an unknown road
+fn check_closure(sdb: &Mutex<Client>, closure: &Closure) -> Result<(), Response> {
+    let (starts_at, ends_at) = match (db::parse_timestamp(&closure.starts_at), db::parse_timestamp(&closure.ends_at)) {
+        (Some(starts_at), Some(ends_at)) => (starts_at, ends_at),
+        _ => return Err(Response::with((status::BadRequest, "starts_at and ends_at must be RFC 3339 timestamps"))),
+    };
+    if ends_at <= starts_at {
+        return Err(Response::with((status::BadRequest, "ends_at must be later than starts_at")));
+    }
+    let network = load_network(sdb)?;
+    if !network.roads.iter().any(|road| road.id == Some(closure.road_id)) {
+        return Err(Response::with((status::BadRequest, format!("Can`t found road {}", closure.road_id))));
+    }
+    Ok(())
+}
+
+
+// The road may have been deleted since it was checked, anything else is the database's fault
+fn closure_write_error(error: &postgres::Error) -> Response {
+    if error.code() == Some(&SqlState::FOREIGN_KEY_VIOLATION) {
+        Response::with((status::BadRequest, "unknown road"))
+    } else {
+        Response::with((status::InternalServerError, "couldn't write record to database"))
+    }
+}
+
+
+// Delete closure with given ID
+pub fn delete_closure(sdb: &Mutex<Client>, request: &mut Request) -> IronResult<Response> {
+    let url: url::Url = request.url.clone().into();
+    let mut path = url.path_segments().unwrap();
+    let sid: &str = path.next_back().unwrap();
+    let id;
+    if let Ok(r) = sid.parse() {
+        id = r;
+    } else {
+        return Ok(Response::with((status::BadRequest, "bad id")));
+    }
+
+    if db::remove_closures(&mut sdb.lock().unwrap(), &[id]).is_ok() {
+        cache::invalidate();
+        Ok(Response::with(status::NoContent))
+    } else {
+        Ok(Response::with((status::NotFound, "couldn't delete record")))
+    }
+}
+
+
+// Shortest path between two cities, optionally through ordered waypoints.
+// Query: from=<city>&to=<city>[&via=<city>,<city>...][&algorithm=dijkstra|astar|bidirectional|ch]
+//        [&optimize=distance|time|cost]        [&avoid_tolls=true|false][&fuel_price=<x>][&fuel_consumption=<x>]
+//        [&avoid_cities=<city>,<city>...][&avoid_roads=<id>,<id>...][&departure_time=<RFC 3339 timestamp>]
+//        [&vehicle=<id>][&weight=<t>][&height=<m>][&hazmat=true|false]
+//        [&mode=road|rail|any][&transfer_penalty=<n>][&format=json|text]
+pub fn get_shortest_path(sdb: &Mutex<Client>, request: &mut Request) -> IronResult<Response> {
+    let params = match query_params(request) {
+        Ok(params) => params,
+        Err(response) => return Ok(response),
+    };
+    let network = match load_network(sdb) {
+        Ok(network) => network,
+        Err(response) => return Ok(response),
+    };
+    let cities = &network.cities;
+    let (from_city, to_city) = match route_endpoints(cities, &params) {
+        Ok(endpoints) => endpoints,
+        Err(response) => return Ok(response),
+    };
+    let as_text = match params.get("format").map(|s| s.as_str()) {
+        None | Some("json") => false,
+        Some("text") => true,
+        Some(_) => return Ok(Response::with((status::BadRequest, "format must be 'json' or 'text'"))),
+    };
+    let algorithm = params.get("algorithm").cloned().unwrap_or_else(|| "dijkstra".to_string());
+    if !["dijkstra", "astar", "bidirectional", "ch"].contains(&algorithm.as_str()) {
+        return Ok(Response::with((status::BadRequest, "algorithm must be 'dijkstra', 'astar', 'bidirectional' or 'ch'")));
+    }
+    let stored_vehicle = match params.get("vehicle").map(|id| id.parse::<i32>()) {
+        None => None,
+        Some(Ok(id)) => match db::get_vehicle(&mut sdb.lock().unwrap(), id) {
+            Ok(Some(vehicle)) => Some(vehicle),
+            Ok(None) => return Ok(Response::with((status::BadRequest, format!("Can`t found vehicle {}", id)))),
+            Err(_) => return Ok(Response::with((status::InternalServerError, "couldn't read vehicle from database"))),
+        },
+        Some(Err(_)) => return Ok(Response::with((status::BadRequest, "vehicle must be a vehicle id"))),
+    };
+    // A vehicle's own modes are the default
+    let default_mode = match stored_vehicle.as_ref().map(|vehicle| vehicle.modes.as_slice()) {
+        Some([Mode::Rail]) => "rail",
+        Some(modes) if modes.contains(&Mode::Rail) => "any",
+        _ => "road",
+    };
+    let (mode, modes) = match params.get("mode").map(|s| s.as_str()).unwrap_or(default_mode) {
+        "road" => ("road", vec![Mode::Road]),
+        "rail" => ("rail", vec![Mode::Rail]),
+        "any" => ("any", vec![Mode::Road, Mode::Rail]),
+        _ => return Ok(Response::with((status::BadRequest, "mode must be 'road', 'rail' or 'any'"))),
+    };
+    if let Some(vehicle) = &stored_vehicle {
+        if let Some(mode) = modes.iter().find(|mode| !vehicle.modes.contains(mode)) {
+            return Ok(Response::with((status::BadRequest,
+                                      format!("vehicle {} can't use {}", vehicle.name, mode.as_str()))));
+        }
+    }
+    let optimize = match params.get("optimize").map(|s| s.as_str()) {
+        None | Some("distance") => Optimize::Distance,
+        Some("time") => Optimize::Time,
+        Some("cost") => Optimize::Cost,
+        Some(_) => return Ok(Response::with((status::BadRequest, "optimize must be 'distance', 'time' or 'cost'"))),
+    };
+    if optimize == Optimize::Cost && mode != "road" {
+        return Ok(Response::with((status::BadRequest, "optimize=cost is only supported with mode=road")));
+    }
+    let avoid_tolls = match params.get("avoid_tolls").map(|s| s.as_str()) {
+        None | Some("false") => false,
+        Some("true") => true,
+        Some(_) => return Ok(Response::with((status::BadRequest, "avoid_tolls must be 'true' or 'false'"))),
+    };
+    let mut fuel = db::routing_params().fuel;
+    if let Some(consumption) = stored_vehicle.as_ref().and_then(|vehicle| vehicle.fuel_consumption) {
+        fuel.consumption = consumption;
+    }
+    let fuel_params = [
+        ("fuel_price", &mut fuel.price, MAX_FUEL_PRICE),
+        ("fuel_consumption", &mut fuel.consumption, MAX_FUEL_CONSUMPTION),
+    ];
+    for (key, value, max) in fuel_params {
+        match params.get(key).map(|p| p.parse::<f64>()) {
+            None => {}
+            Some(Ok(number)) if (0.0..=max).contains(&number) => *value = number,
+            Some(_) => return Ok(Response::with((status::BadRequest, format!("{} must be a number from 0 to {}", key, max)))),
+        }
+    }
+    let mut vehicle = match &stored_vehicle {
+        Some(stored) => VehicleProfile {
+            weight: stored.weight,
+            height: stored.height,
+            hazmat: stored.hazmat,
+            speed: stored.average_speed,
+        },
+        None => VehicleProfile::default(),
+    };
+    for (key, value) in [("weight", &mut vehicle.weight), ("height", &mut vehicle.height)] {
+        match params.get(key).map(|p| p.parse::<f64>()) {
+            None => {}
+            Some(Ok(number)) if number > 0.0 => *value = Some(number),
+            Some(_) => return Ok(Response::with((status::BadRequest, format!("{} must be a positive number", key)))),
+        }
+    }
+    vehicle.hazmat = match params.get("hazmat").map(|s| s.as_str()) {
+        None => vehicle.hazmat,
+        Some("false") => false,
+        Some("true") => true,
+        Some(_) => return Ok(Response::with((status::BadRequest, "hazmat must be 'true' or 'false'"))),
+    };
+    let vehicle = if stored_vehicle.is_some() || vehicle.weight.is_some() || vehicle.height.is_some() || vehicle.hazmat {
+        Some(vehicle)
+    } else {
+        None
+    };
+    let transfer_penalty: i32 = match params.get("transfer_penalty").map(|p| p.parse()) {
+        None => db::routing_params().transfer_penalty,
+        Some(Ok(penalty)) if penalty >= 0 => penalty,
+        Some(_) => return Ok(Response::with((status::BadRequest, "transfer_penalty must be a non-negative number"))),
+    };
+    let via = params.list("via");
+    for waypoint in &via {
+        if !cities.contains_key(waypoint) {
+            return Ok(Response::with((status::BadRequest,
+                                      format!("Can`t found waypoint city {}", waypoint))));
+        }
+    }
+    let mut avoid_cities = Vec::new();
+    for city in params.list("avoid_cities") {
+        if !cities.contains_key(&city) {
+            return Ok(Response::with((status::BadRequest, format!("Can`t found avoided city {}", city))));
+        }
+        if city == from_city || city == to_city || via.contains(&city) {
+            return Ok(Response::with((status::BadRequest, format!("{} is both a stop and avoided", city))));
+        }
+        if !avoid_cities.contains(&city) {
+            avoid_cities.push(city);
+        }
+    }
+    let mut avoid_roads = Vec::new();
+    for id in params.list("avoid_roads") {
+        match id.parse::<i32>() {
+            Ok(id) if !avoid_roads.contains(&id) => avoid_roads.push(id),
+            Ok(_) => {}
+            Err(_) => return Ok(Response::with((status::BadRequest, "avoid_roads must be a list of road ids"))),
+        }
+    }
+    if let Some(id) = avoid_roads.iter().find(|&&id| !network.roads.iter().any(|road| road.id == Some(id))) {
+        return Ok(Response::with((status::BadRequest, format!("Can`t found avoided road {}", id))));
+    }
+    // Roads closed when the trip starts
+    let closed_roads = match closed_roads(&network, &params) {
+        Ok(roads) => roads,
+        Err(response) => return Ok(response),
+    };
+
+    let options = GraphOptions {
+        modes,
+        optimize,
+        fuel,
+        avoid_tolls,
+        avoid_cities: avoid_cities.iter().cloned().collect(),
+        avoid_roads: avoid_roads.iter().chain(&closed_roads).copied().collect(),
+        vehicle,
+        ..road_options()
+    };
+    let graph = match network_graph(&network, &options) {
+        Ok(graph) => graph,
+        Err(response) => return Ok(response),
+    };
+    let with_transfers = options.modes.len() > 1 && transfer_penalty > 0;
+    let indexed_search = algorithm == "bidirectional" || algorithm == "ch";
+    if with_transfers && indexed_search {
+        return Ok(Response::with((status::BadRequest, "transfer_penalty is only supported by 'dijkstra' and 'astar'")));
+    }
+    let (indexed, hierarchy) = match algorithm.as_str() {
+        "ch" => {
+            let (indexed, hierarchy) = network.hierarchy(&options, &graph);
+            (Some(indexed), Some(hierarchy))
+        }
+        "bidirectional" => (Some(network.indexed(&options, &graph)), None),
+        _ => (None, None),
+    };
+    // Dense networks are searched faster as a matrix
+    let dense = if algorithm == "dijkstra" && !with_transfers { network.dense(&options, &graph) } else { None };
+    let cost_per_km = min_cost_per_km(&graph, db::routing_params().length_unit_km);
+    let mut stops = vec![from_city.clone()];
+    stops.extend(via.iter().cloned());
+    stops.push(to_city.clone());
+
+    // Stitch separate searches between consecutive stops
+    let mut legs: Vec<(String, Edge)> = Vec::new();
+    let mut segments = Vec::new();
+    let mut nodes_expanded = 0;
+    for pair in stops.windows(2) {
+        let (start, goal) = (&pair[0], &pair[1]);
+        let heuristic: Box<dyn Fn(&str) -> i32> = if algorithm == "astar" {
+            Box::new(great_circle_heuristic(cities, &cities[goal], cost_per_km))
+        } else {
+            Box::new(|_| 0)
+        };
+        let (segment, expanded) = if with_transfers {
+            // Continue in the mode the previous segment arrived by, a change at the waypoint is a transfer too
+            let arrival_mode = legs.last().map(|(_, edge)| edge.mode);
+            multimodal_search(start, goal, arrival_mode, &graph, transfer_penalty, heuristic)
+        } else if let Some(dense) = &dense {
+            let path = dense.paths(start);
+            (trace_path(start, goal, &path), path.len())
+        } else if let Some(indexed) = &indexed {
+            let (start, goal) = (indexed.index[start], indexed.index[goal]);
+            let (edges, expanded) = match &hierarchy {
+                Some(hierarchy) => hierarchy.query(start, goal),
+                None => indexed.bidirectional_dijkstra(start, goal),
+            };
+            (edges.map(|edges| indexed.legs(&edges)), expanded)
         } else {
-            return Ok(Response::with((status::InternalServerError, "Couldn't get Roads data")));
+            let (path, expanded) = astar(start, goal, &graph, heuristic);
+            (trace_path(start, goal, &path), expanded)
+        };
+        nodes_expanded += expanded;
+
+        let segment = match segment {
+            Some(segment) => segment,
+            None if options.vehicle.is_some() => return blocked_response(&network, &options, start, goal),
+            None => return unreachable_response(&graph, start, goal),
+        };
+        segments.push((start.clone(), goal.clone(), segment.clone()));
+        legs.extend(segment);
+    }
+    let distance = path_length(&legs);
+    let duration = path_duration(&legs);
+    let toll = path_toll(&legs);
+    let cost = path_cost(&legs, &fuel, db::routing_params().length_unit_km);
+
+    if as_text {
+        let massage = format_path(&from_city, &to_city, &legs);
+        return json_response(status::Ok, &format!("{msg}\nPath length: {cost}", msg=massage, cost=distance));
+    }
+
+    let (route, legs) = describe_route(&from_city, legs, cities);
+    let segments = segments.into_iter().map(|(from, to, segment)| {
+        let (distance, duration) = (path_length(&segment), path_duration(&segment));
+        let (_, legs) = describe_route(&from, segment, cities);
+        Segment { from, to, legs, distance, duration }
+    }).collect();
+    let response = PathResponse {
+        from: from_city,
+        to: to_city,
+        via,
+        path: route,
+        legs,
+        segments,
+        distance,
+        duration,
+        toll,
+        cost: (cost * 100.0).round() / 100.0,
+        optimize: optimize.as_str().to_string(),
+        avoid_cities,
+        avoid_roads,
+        departure_time: params.get("departure_time").cloned(),
+        closed_roads,
+        vehicle_id: stored_vehicle.and_then(|vehicle| vehicle.id),
+        graph_version: network.version,
+        algorithm: if dense.is_some() { "dense".to_string() } else { algorithm },
+        mode: mode.to_string(),
+        nodes_expanded,
+    };
+    json_response(status::Ok, &response)
+}
+
+
+// Up to `k` shortest loopless paths between two cities (Yen's algorithm).
+// Query: from=<city>&to=<city>[&k=3][&departure_time=<RFC 3339 timestamp>]
+pub fn get_alternative_paths(sdb: &Mutex<Client>, request: &mut Request) -> IronResult<Response> {
+    let params = match query_params(request) {
+        Ok(params) => params,
+        Err(response) => return Ok(response),
+    };
+    let (network, _, graph) = match load_open_roads(sdb, &params) {
+        Ok(network) => network,
+        Err(response) => return Ok(response),
+    };
+    let cities = &network.cities;
+    let (from_city, to_city) = match route_endpoints(cities, &params) {
+        Ok(endpoints) => endpoints,
+        Err(response) => return Ok(response),
+    };
+    let k: usize = match params.get("k").map(|k| k.parse()) {
+        None => 3,
+        Some(Ok(k)) if (1..=MAX_ALTERNATIVES).contains(&k) => k,
+        Some(_) => return Ok(Response::with((status::BadRequest,
+                                             format!("k must be a number from 1 to {}", MAX_ALTERNATIVES)))),
+    };
+
+    let paths = k_shortest_paths(&from_city, &to_city, &graph, k);
+    if paths.is_empty() {
+        return unreachable_response(&graph, &from_city, &to_city);
+    }
+    let best: HashSet<i32> = paths[0].iter().map(|(_, edge)| edge.road_id).collect();
+    let best_distance = path_length(&paths[0]);
+
+    let routes = paths.into_iter().map(|legs| {
+        let distance = path_length(&legs);
+        let shared_length: i32 = legs.iter()
+            .filter(|(_, edge)| best.contains(&edge.road_id))
+            .map(|(_, edge)| edge.length)
+            .sum();
+        let (path, legs) = describe_route(&from_city, legs, cities);
+        AlternativeRoute {
+            path,
+            legs,
+            distance,
+            shared_length,
+            overlap: if best_distance > 0 { shared_length as f64 / best_distance as f64 } else { 1.0 },
+        }
+    }).collect();
+
+    json_response(status::Ok, &AlternativesResponse { from: from_city, to: to_city, routes })
+}
+
+
+// Shortest round trip from a start city through every stop and back.
+// Body: {"start": <city>, "stops": [<city>, ...]}
+// Query: [departure_time=<RFC 3339 timestamp>]
+pub fn get_tour(sdb: &Mutex<Client>, request: &mut Request) -> IronResult<Response> {
+    let params = match query_params(request) {
+        Ok(params) => params,
+        Err(response) => return Ok(response),
+    };
+    let mut body = String::new();
+    request.body.read_to_string(&mut body).unwrap();
+    let decoded: serde_json::Result<TourRequest> = serde_json::from_str(&body);
+    let tour = match decoded {
+        Ok(tour) => tour,
+        Err(_) => return Ok(Response::with((status::BadRequest, "couldn't decode JSON"))),
+    };
+
+    // The start city is point 0 of the distance matrix
+    let mut points = vec![tour.start];
+    for stop in tour.stops {
+        if !points.contains(&stop) {
+            points.push(stop);
+        }
+    }
+    if points.len() < 2 {
+        return Ok(Response::with((status::BadRequest, "at least one stop other than start is required")));
+    }
+    let max_stops = db::routing_params().tsp_max_stops;
+    if points.len() - 1 > max_stops {
+        return Ok(Response::with((status::BadRequest, format!("at most {} stops are allowed", max_stops))));
+    }
+    let (network, options, graph) = match load_open_roads(sdb, &params) {
+        Ok(network) => network,
+        Err(response) => return Ok(response),
+    };
+    let cities = &network.cities;
+    for point in &points {
+        if !cities.contains_key(point) {
+            return Ok(Response::with((status::BadRequest, format!("Can`t found city {}", point))));
         }
+    }
+
+    let dense = network.dense(&options, &graph);
+    let trees: Vec<PathMap> = points.iter()
+        .map(|point| shortest_path_tree(point, &graph, dense.as_deref()))
+        .collect();
+    let mut dist = vec![vec![0; points.len()]; points.len()];
+    for (i, tree) in trees.iter().enumerate() {
+        for (j, point) in points.iter().enumerate() {
+            match tree.get(point) {
+                Some((_, cost)) => dist[i][j] = *cost,
+                None => return unreachable_response(&graph, &points[i], point),
+            }
+        }
+    }
+
+    let (order, method) = if points.len() - 1 <= db::routing_params().tsp_exact_limit {
+        (held_karp(&dist), "held-karp")
     } else {
-        return Ok(Response::with((status::InternalServerError, "Can`t load Cities data.")));
+        (tsp_heuristic(&dist), "2-opt/or-opt")
+    };
+
+    let mut legs = Vec::new();
+    let mut segments = Vec::new();
+    for (&a, &b) in order.iter().zip(order.iter().cycle().skip(1)) {
+        let segment = trace_path(&points[a], &points[b], &trees[a]).unwrap();
+        let (distance, duration) = (path_length(&segment), path_duration(&segment));
+        let (_, segment_legs) = describe_route(&points[a], segment.clone(), cities);
+        segments.push(Segment {
+            from: points[a].clone(),
+            to: points[b].clone(),
+            legs: segment_legs,
+            distance,
+            duration,
+        });
+        legs.extend(segment);
     }
-    let content_type = Mime(TopLevel::Application, SubLevel::Json, Vec::new());
+    let (path, legs) = describe_route(&points[0], legs, cities);
 
-    Ok(Response::with((content_type, status::Ok, json_records.unwrap())))
+    let mut visit_order: Vec<String> = order.iter().map(|&i| points[i].clone()).collect();
+    visit_order.push(points[0].clone());
+    let response = TourResponse {
+        start: points[0].clone(),
+        order: visit_order,
+        method: method.to_string(),
+        path,
+        legs,
+        segments,
+        distance: tour_length(&dist, &order),
+    };
+    json_response(status::Ok, &response)
+}
+
+
+// Every city within a distance budget from the start city.
+// Query: from=<city>&max_distance=<n>[&departure_time=<RFC 3339 timestamp>]
+pub fn get_reachable(sdb: &Mutex<Client>, request: &mut Request) -> IronResult<Response> {
+    let params = match query_params(request) {
+        Ok(params) => params,
+        Err(response) => return Ok(response),
+    };
+    let from_city = match params.get("from") {
+        Some(from) => from.clone(),
+        None => return Ok(Response::with((status::BadRequest, "'from' parameter is required"))),
+    };
+    let max_distance: i32 = match params.get("max_distance").map(|d| d.parse()) {
+        Some(Ok(distance)) if distance >= 0 => distance,
+        _ => return Ok(Response::with((status::BadRequest, "max_distance must be a non-negative number"))),
+    };
+
+    let (network, _, graph) = match load_open_roads(sdb, &params) {
+        Ok(network) => network,
+        Err(response) => return Ok(response),
+    };
+    let cities = &network.cities;
+    if !cities.contains_key(&from_city) {
+        return Ok(Response::with((status::BadRequest, "Can`t found start city with given parameters")));
+    }
+
+    let path = dijkstra_bounded(&from_city, max_distance, &graph);
+    let response = ReachableResponse {
+        from: from_city,
+        max_distance,
+        cities: reached_cities(path, cities),
+    };
+    json_response(status::Ok, &response)
+}
+
+
+// Shortest path tree: distance and predecessor of every city reachable from the start city.
+// Query: from=<city>[&departure_time=<RFC 3339 timestamp>]
+pub fn get_path_tree(sdb: &Mutex<Client>, request: &mut Request) -> IronResult<Response> {
+    let params = match query_params(request) {
+        Ok(params) => params,
+        Err(response) => return Ok(response),
+    };
+    let from_city = match params.get("from") {
+        Some(from) => from.clone(),
+        None => return Ok(Response::with((status::BadRequest, "'from' parameter is required"))),
+    };
+
+    let (network, options, graph) = match load_open_roads(sdb, &params) {
+        Ok(network) => network,
+        Err(response) => return Ok(response),
+    };
+    if !network.cities.contains_key(&from_city) {
+        return Ok(Response::with((status::BadRequest, "Can`t found start city with given parameters")));
+    }
+
+    let dense = network.dense(&options, &graph);
+    let path = shortest_path_tree(&from_city, &graph, dense.as_deref());
+    let response = PathTreeResponse {
+        from: from_city,
+        graph_version: network.version,
+        cities: reached_cities(path, &network.cities),
+    };
+    json_response(status::Ok, &response)
+}
+
+
+// Cities tagged as facilities
+pub fn get_facilities(sdb: &Mutex<Client>, _request: &mut Request) -> IronResult<Response> {
+    let network = match load_network(sdb) {
+        Ok(network) => network,
+        Err(response) => return Ok(response),
+    };
+    let facilities: Vec<&City> = network.nodes.iter().filter(|city| city.facility).collect();
+    json_response(status::Ok, &facilities)
+}
+
+
+// Facilities nearest to a city by road distance, nearest first.
+// Query: from=<city>[&k=3]
+pub fn get_nearest_facilities(sdb: &Mutex<Client>, request: &mut Request) -> IronResult<Response> {
+    let params = match query_params(request) {
+        Ok(params) => params,
+        Err(response) => return Ok(response),
+    };
+    let from_city = match params.get("from") {
+        Some(from) => from.clone(),
+        None => return Ok(Response::with((status::BadRequest, "'from' parameter is required"))),
+    };
+    let k: usize = match params.get("k").map(|k| k.parse()) {
+        None => 3,
+        Some(Ok(k)) if k >= 1 => k,
+        Some(_) => return Ok(Response::with((status::BadRequest, "k must be a positive number"))),
+    };
+
+    let (network, graph) = match load_graph(sdb, &road_options()) {
+        Ok(network) => network,
+        Err(response) => return Ok(response),
+    };
+    let cities = &network.cities;
+    if !cities.contains_key(&from_city) {
+        return Ok(Response::with((status::BadRequest, "Can`t found start city with given parameters")));
+    }
+
+    let facilities: HashSet<String> = network.nodes.iter()
+        .filter(|city| city.facility)
+        .map(|city| city.name.clone())
+        .collect();
+    let nearest = nearest_facilities(&from_city, &facilities, k, &graph);
+    let response = NearestFacilitiesResponse {
+        from: from_city,
+        facilities: nearest.into_iter()
+            .map(|(name, distance)| NearbyFacility { city: cities[&name].clone(), distance })
+            .collect(),
+    };
+    json_response(status::Ok, &response)
+}
+
+
+// Every city assigned to the facility nearest to it by road distance (network Voronoi partition)
+pub fn get_facility_assignment(sdb: &Mutex<Client>, _request: &mut Request) -> IronResult<Response> {
+    let (network, graph) = match load_graph(sdb, &road_options()) {
+        Ok(network) => network,
+        Err(response) => return Ok(response),
+    };
+    let mut facilities: Vec<String> = network.nodes.iter()
+        .filter(|city| city.facility)
+        .map(|city| city.name.clone())
+        .collect();
+    facilities.sort();
+
+    // Search back from the facilities to get distances from the cities to them
+    let nearest = multi_source_dijkstra(&facilities, &reverse_graph(&graph));
+    let mut cities: Vec<FacilityAssignment> = network.nodes.iter().map(|city| {
+        let (facility, distance) = match nearest.get(&city.name) {
+            Some((facility, distance)) => (Some(facility.clone()), Some(*distance)),
+            None => (None, None),
+        };
+        FacilityAssignment { city: city.name.clone(), facility, distance }
+    }).collect();
+    cities.sort_by(|a, b| a.city.cmp(&b.city));
+    json_response(status::Ok, &AssignmentResponse { facilities, cities })
+}
+
+
+// Cities where opening k more facilities minimises the total road distance from every city to its nearest facility (k-median).
+// Query: k=<n>[&weighted=true|false][&keep_existing=true|false][&candidates=<city>,<city>...]
+pub fn get_facility_placement(sdb: &Mutex<Client>, request: &mut Request) -> IronResult<Response> {
+    let params = match query_params(request) {
+        Ok(params) => params,
+        Err(response) => return Ok(response),
+    };
+    let k: usize = match params.get("k").map(|k| k.parse()) {
+        Some(Ok(k)) if k >= 1 => k,
+        _ => return Ok(Response::with((status::BadRequest, "k must be a positive number"))),
+    };
+    let weighted = match params.get("weighted").map(|s| s.as_str()) {
+        None | Some("false") => false,
+        Some("true") => true,
+        Some(_) => return Ok(Response::with((status::BadRequest, "weighted must be 'true' or 'false'"))),
+    };
+    let keep_existing = match params.get("keep_existing").map(|s| s.as_str()) {
+        None | Some("true") => true,
+        Some("false") => false,
+        Some(_) => return Ok(Response::with((status::BadRequest, "keep_existing must be 'true' or 'false'"))),
+    };
+
+    let (network, graph) = match load_graph(sdb, &road_options()) {
+        Ok(network) => network,
+        Err(response) => return Ok(response),
+    };
+    let mut nodes: Vec<&City> = network.nodes.iter().collect();
+    nodes.sort_by(|a, b| a.name.cmp(&b.name));
+    let existing: Vec<&City> = nodes.iter().copied().filter(|city| keep_existing && city.facility).collect();
+    let mut candidates: Vec<&City> = Vec::new();
+    for name in params.list("candidates") {
+        let city = match network.cities.get(&name) {
+            Some(city) => city,
+            None => return Ok(Response::with((status::BadRequest, format!("Can`t found candidate city {}", name)))),
+        };
+        if existing.contains(&city) {
+            return Ok(Response::with((status::BadRequest, format!("{} is already a facility", name))));
+        }
+        if !candidates.contains(&city) {
+            candidates.push(city);
+        }
+    }
+    if !params.contains_key("candidates") {
+        candidates = nodes.iter().copied().filter(|city| !existing.contains(city)).collect();
+    }
+    if k > candidates.len() {
+        return Ok(Response::with((status::BadRequest, format!("only {} cities can be chosen", candidates.len()))));
+    }
+    // Every candidate costs a search over the network and a row of distances to every city,
+    // and each of the k greedy rounds and swap passes tries every candidate against every city
+    let limit = db::routing_params().placement_limit;
+    if k.saturating_mul(candidates.len()).saturating_mul(nodes.len()) > limit {
+        return Ok(Response::with((status::BadRequest, format!(
+            "k {} x {} candidates x {} cities is over placement_limit of {}, lower k or narrow the candidates down with candidates=",
+            k, candidates.len(), nodes.len(), limit))));
+    }
+    let weights: Vec<i64> = nodes.iter()
+        .map(|city| if weighted { city.population.unwrap_or(0) as i64 } else { 1 })
+        .collect();
+    if weights.iter().all(|&weight| weight == 0) {
+        return Ok(Response::with((status::BadRequest, "weighted=true needs cities with a population")));
+    }
+
+    // Distances from the cities to the facilities, by searching back from the facilities.
+    // The existing ones are never closed, so only the nearest of them matters to each city.
+    let reversed = reverse_graph(&graph);
+    let existing_names: Vec<String> = existing.iter().map(|city| city.name.clone()).collect();
+    let nearest_existing = multi_source_dijkstra(&existing_names, &reversed);
+    let existing_distances: Vec<Vec<Option<i32>>> = if existing.is_empty() {
+        Vec::new()
+    } else {
+        vec![nodes.iter().map(|city| nearest_existing.get(&city.name).map(|(_, distance)| *distance)).collect()]
+    };
+    let candidate_distances: Vec<Vec<Option<i32>>> = candidates.iter().map(|candidate| {
+        let tree = dijkstra_all(&candidate.name, &reversed);
+        nodes.iter().map(|city| tree.get(&city.name).map(|(_, distance)| *distance)).collect()
+    }).collect();
+    let (chosen, (unserved, total_distance)) = k_median(&candidate_distances, &existing_distances, &weights, k);
+
+    let cities = nodes.iter().enumerate().map(|(i, city)| {
+        let nearest = nearest_existing.get(&city.name)
+            .map(|(facility, distance)| (*distance, facility))
+            .into_iter()
+            .chain(chosen.iter().filter_map(|&c| candidate_distances[c][i].map(|distance| (distance, &candidates[c].name))))
+            .min();
+        FacilityAssignment {
+            city: city.name.clone(),
+            facility: nearest.map(|(_, facility)| facility.clone()),
+            distance: nearest.map(|(distance, _)| distance),
+        }
+    }).collect();
+    let response = PlacementResponse {
+        k,
+        weighted,
+        existing: existing.iter().map(|city| city.name.clone()).collect(),
+        chosen: chosen.iter().map(|&i| candidates[i].name.clone()).collect(),
+        total_distance,
+        unserved,
+        cities,
+    };
+    json_response(status::Ok, &response)
+}
+
+
+// Shortest distances between every origin and destination, null (empty in CSV) if unreachable.
+// Body: {"origins": [<city name or id>, ...], "destinations": [...]}
+// Query: [format=json|csv][&departure_time=<RFC 3339 timestamp>]
+pub fn get_distance_matrix(sdb: &Mutex<Client>, request: &mut Request) -> IronResult<Response> {
+    let params = match query_params(request) {
+        Ok(params) => params,
+        Err(response) => return Ok(response),
+    };
+    let as_csv = match params.get("format").map(|s| s.as_str()) {
+        None | Some("json") => false,
+        Some("csv") => true,
+        Some(_) => return Ok(Response::with((status::BadRequest, "format must be 'json' or 'csv'"))),
+    };
+    let mut body = String::new();
+    request.body.read_to_string(&mut body).unwrap();
+    let decoded: serde_json::Result<MatrixRequest> = serde_json::from_str(&body);
+    let matrix = match decoded {
+        Ok(matrix) => matrix,
+        Err(_) => return Ok(Response::with((status::BadRequest, "couldn't decode JSON"))),
+    };
+
+    let (network, options, graph) = match load_open_roads(sdb, &params) {
+        Ok(network) => network,
+        Err(response) => return Ok(response),
+    };
+    let cities = &network.cities;
+    let resolve = |refs: &[CityRef]| -> Result<Vec<String>, String> {
+        refs.iter().map(|city| match city {
+            CityRef::Id(id) => cities.values()
+                .find(|node| node.id == Some(*id))
+                .map(|node| node.name.clone())
+                .ok_or(format!("Can`t found city with id {}", id)),
+            CityRef::Name(name) => cities.get(name)
+                .map(|node| node.name.clone())
+                .ok_or(format!("Can`t found city {}", name)),
+        }).collect()
+    };
+    let (origins, destinations) = match (resolve(&matrix.origins), resolve(&matrix.destinations)) {
+        (Ok(origins), Ok(destinations)) => (origins, destinations),
+        (Err(message), _) | (_, Err(message)) => return Ok(Response::with((status::BadRequest, message))),
+    };
+
+    let dense = network.dense(&options, &graph);
+    let mut trees: HashMap<&String, PathMap> = HashMap::new();
+    for origin in &origins {
+        if !trees.contains_key(origin) {
+            trees.insert(origin, shortest_path_tree(origin, &graph, dense.as_deref()));
+        }
+    }
+    let distances: Vec<Vec<Option<i32>>> = origins.iter().map(|origin| {
+        destinations.iter()
+            .map(|destination| trees[origin].get(destination).map(|(_, cost)| *cost))
+            .collect()
+    }).collect();
+
+    if as_csv {
+        let mut csv = String::from("origin");
+        for destination in &destinations {
+            csv += &format!(",{}", csv_field(destination));
+        }
+        for (origin, row) in origins.iter().zip(&distances) {
+            csv += &format!("\n{}", csv_field(origin));
+            for distance in row {
+                csv += &format!(",{}", distance.map(|d| d.to_string()).unwrap_or_default());
+            }
+        }
+        csv.push('\n');
+        let content_type = Mime(TopLevel::Text, SubLevel::Ext("csv".to_string()), Vec::new());
+        return Ok(Response::with((content_type, status::Ok, csv)));
+    }
+
+    json_response(status::Ok, &MatrixResponse { origins, destinations, distances })
+}
+
+
+// Connected components, critical roads and critical cities of the road network
+pub fn get_graph_analysis(sdb: &Mutex<Client>, _request: &mut Request) -> IronResult<Response> {
+    let (_, graph) = match load_graph(sdb, &road_options()) {
+        Ok(network) => network,
+        Err(response) => return Ok(response),
+    };
+    json_response(status::Ok, &analytics::analyze(&graph))
+}
+
+
+// Cheapest set of roads keeping every connected component connected
+pub fn get_spanning_tree(sdb: &Mutex<Client>, _request: &mut Request) -> IronResult<Response> {
+    let network = match load_network(sdb) {
+        Ok(network) => network,
+        Err(response) => return Ok(response),
+    };
+    match analytics::minimum_spanning_forest(&network.nodes, &network.roads) {
+        Ok(forest) => json_response(status::Ok, &forest),
+        Err(message) => Ok(Response::with((status::InternalServerError, message))),
+    }
+}
+
+
+// Check 'from' and 'to' query parameters name existing cities
+fn route_endpoints(cities: &HashMap<String, City>, params: &QueryParams) -> Result<(String, String), Response> {
+    let (from_city, to_city) = match (params.get("from"), params.get("to")) {
+        (Some(from), Some(to)) => (from.clone(), to.clone()),
+        _ => return Err(Response::with((status::BadRequest,
+                                        "both 'from' and 'to' parameters are required"))),
+    };
+    if !cities.contains_key(&from_city) {
+        return Err(Response::with((status::BadRequest, "Can`t found start city with given parameters")));
+    }
+    if !cities.contains_key(&to_city) {
+        return Err(Response::with((status::BadRequest, "Can`t found destination city with given parameters")));
+    }
+    Ok((from_city, to_city))
+}
+
+
+// Cities, roads and railways to build graphs from, cached until the next write
+fn load_network(sdb: &Mutex<Client>) -> Result<Arc<Network>, Response> {
+    cache::network(sdb).map_err(|message| Response::with((status::InternalServerError, message)))
+}
+
+
+// Network and its graph for `options`
+fn load_graph(sdb: &Mutex<Client>, options: &GraphOptions) -> Result<(Arc<Network>, Arc<Graph>), Response> {
+    let network = load_network(sdb)?;
+    let graph = network_graph(&network, options)?;
+    Ok((network, graph))
+}
+
+
+// Graph of an already loaded network for `options`
+fn network_graph(network: &Network, options: &GraphOptions) -> Result<Arc<Graph>, Response> {
+    network.graph(options).map_err(|message| Response::with((status::InternalServerError, message)))
+}
+
+
+// Roads closed at `departure_time`, now if not given
+fn closed_roads(network: &Network, params: &QueryParams) -> Result<Vec<i32>, Response> {
+    let at = match params.get("departure_time") {
+        Some(text) => db::parse_timestamp(text)
+            .ok_or_else(|| Response::with((status::BadRequest, "departure_time must be an RFC 3339 timestamp")))?,
+        None => db::now_timestamp(),
+    };
+    Ok(network.closed_roads(at))
+}
+
+
+// Network and its road graph without the roads closed at `departure_time`, with the options the graph was built for
+fn load_open_roads(sdb: &Mutex<Client>, params: &QueryParams)
+    -> Result<(Arc<Network>, GraphOptions, Arc<Graph>), Response> {
+    let network = load_network(sdb)?;
+    let options = GraphOptions {
+        avoid_roads: closed_roads(&network, params)?.into_iter().collect(),
+        ..road_options()
+    };
+    let graph = network_graph(&network, &options)?;
+    Ok((network, options, graph))
+}
+
+
+// 404 explaining why `to` can't be reached from `from`
+fn unreachable_response(graph: &Graph, from: &str, to: &str) -> IronResult<Response> {
+    let components = connected_components(graph);
+    let describe = |city: &str| {
+        let component = components[city];
+        CityComponent {
+            city: city.to_string(),
+            component,
+            component_size: components.values().filter(|&&c| c == component).count(),
+        }
+    };
+    let (from, to) = (describe(from), describe(to));
+    let error = if from.component == to.component {
+        format!("{} can't be reached from {}", to.city, from.city)
+    } else {
+        format!("{} and {} are disconnected", from.city, to.city)
+    };
+
+    json_response(status::NotFound, &UnreachableResponse { error, from, to })
+}
+
+
+// 404 naming the tightest road limit on the shortest route the vehicle of `options` can't take
+fn blocked_response(network: &Network, options: &GraphOptions, from: &str, to: &str) -> IronResult<Response> {
+    let vehicle = options.vehicle.clone().unwrap_or_default();
+    let unrestricted = GraphOptions { vehicle: None, ..options.clone() };
+    let graph = match network_graph(network, &unrestricted) {
+        Ok(graph) => graph,
+        Err(response) => return Ok(response),
+    };
+    let (path, _) = dijkstra(from, to, &graph);
+    let legs = match trace_path(from, to, &path) {
+        Some(legs) => legs,
+        None => return unreachable_response(&graph, from, to),
+    };
+    let blocked_by = legs.iter()
+        .filter(|(_, edge)| edge.mode == Mode::Road)
+        .filter_map(|(_, edge)| network.roads.iter().find(|road| road.id == Some(edge.road_id)))
+        .filter_map(|road| vehicle.restriction(road))
+        .max_by(|(a, _), (b, _)| a.total_cmp(b))
+        .map(|(_, restriction)| restriction);
+    let blocked_by = match blocked_by {
+        Some(restriction) => restriction,
+        None => return unreachable_response(&graph, from, to),
+    };
+
+    let error = match (blocked_by.limit, blocked_by.vehicle) {
+        (Some(limit), Some(value)) => format!("no route for this vehicle from {} to {}: road {} allows {} up to {}, vehicle has {}",
+                                              from, to, blocked_by.road_id, blocked_by.constraint, limit, value),
+        _ => format!("no route for this vehicle from {} to {}: road {} is closed to hazardous goods",
+                     from, to, blocked_by.road_id),
+    };
+    json_response(status::NotFound, &BlockedResponse { error, from: from.to_string(), to: to.to_string(), blocked_by })
+}
+
+
+// Cities visited and legs driven along the path found by `trace_path`
+fn describe_route(start: &str, legs: Vec<(String, Edge)>, cities: &HashMap<String, City>) -> (Vec<City>, Vec<Leg>) {
+    let mut route = vec![cities[start].clone()];
+    route.extend(legs.iter().map(|(_, edge)| cities[&edge.to].clone()));
+    let legs = legs.into_iter()
+        .map(|(from, edge)| Leg {
+            road_id: edge.road_id,
+            mode: edge.mode,
+            from,
+            to: edge.to,
+            length: edge.length,
+            duration: edge.duration,
+            toll: edge.toll,
+        })
+        .collect();
+    (route, legs)
+}
+
+
+// Cities of a search result with their predecessors, nearest first
+fn reached_cities(path: PathMap, cities: &HashMap<String, City>) -> Vec<ReachedCity> {
+    let mut reached: Vec<ReachedCity> = path.into_iter().map(|(name, (prev, distance))| {
+        let (predecessor, road_id) = match prev {
+            Some((city, edge)) => (Some(city), Some(edge.road_id)),
+            None => (None, None),
+        };
+        ReachedCity { city: cities[&name].clone(), distance, predecessor, road_id }
+    }).collect();
+    reached.sort_by(|a, b| a.distance.cmp(&b.distance).then_with(|| a.city.name.cmp(&b.city.name)));
+    reached
+}
+
+
+// Quote a CSV field if it has separators or quotes in it
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+
+// Parameters documented as lists, the only ones that may be repeated
+const LIST_PARAMS: [&str; 4] = ["via", "avoid_cities", "avoid_roads", "candidates"];
+
+// Query parameters of a request by name
+struct QueryParams(HashMap<String, Vec<String>>);
+
+impl QueryParams {
+    fn get(&self, key: &str) -> Option<&String> {
+        self.0.get(key).map(|values| &values[0])
+    }
+
+    fn contains_key(&self, key: &str) -> bool {
+        self.0.contains_key(key)
+    }
+
+    // Items of a list parameter, empty when missing. A single value is split on commas,
+    // repeated ones (via=A&via=B) are taken whole, so names with commas can be given that way
+    fn list(&self, key: &str) -> Vec<String> {
+        let items: Vec<&str> = match self.0.get(key).map(|values| values.as_slice()) {
+            None => Vec::new(),
+            Some([value]) => value.split(',').collect(),
+            Some(values) => values.iter().map(|value| value.as_str()).collect(),
+        };
+        items.into_iter()
+            .map(|item| item.trim().to_string())
+            .filter(|item| !item.is_empty())
+            .collect()
+    }
+}
+
+
+// Collect query parameters, 400 if one that isn't a list is given more than once
+fn query_params(request: &Request) -> Result<QueryParams, Response> {
+    let url: url::Url = request.url.clone().into();
+    let mut params: HashMap<String, Vec<String>> = HashMap::new();
+    for (key, value) in url.query_pairs() {
+        let values = params.entry(key.to_string()).or_default();
+        if !values.is_empty() && !LIST_PARAMS.contains(&key.as_ref()) {
+            return Err(Response::with((status::BadRequest, format!("{} is given more than once", key))));
+        }
+        values.push(value.to_string());
+    }
+    Ok(QueryParams(params))
+}
+
+
+fn json_response<T: Serialize>(code: status::Status, value: &T) -> IronResult<Response> {
+    if let Ok(json) = serde_json::to_string(value) {
+        let content_type = Mime(TopLevel::Application, SubLevel::Json, Vec::new());
+        Ok(Response::with((content_type, code, json)))
+    } else {
+        Ok(Response::with((status::InternalServerError, "couldn't convert records to JSON")))
+    }
 }
 
 
@@ -371,11 +1632,55 @@ Cities records:
     [get, /api/v1/cities, handlers::get_cities, get_cities],
     [get, /api/v1/cities/:name, handlers::get_city, get_city],
     [post, /api/v1/cities, handlers::add_city, add_city],
-    [delete, /api/v1/cities/:id, handlers::delete_city, delete_city]
+    [delete, /api/v1/cities/:id, handlers::delete_city, delete_city],
+    [post, /api/v1/cities/:id/facility, handlers::add_facility, add_facility],
+    [delete, /api/v1/cities/:id/facility, handlers::remove_facility, remove_facility]
 Roads records:
     [get, /api/v1/roads, handlers::get_roads, get_roads],
     [post, /api/v1/roads, handlers::add_road, add_road],
     [delete, /api/v1/roads/:id, handlers::delete_road, delete_road]
+Railways records:
+    [get, /api/v1/railways, handlers::get_railways, get_railways],
+    [post, /api/v1/railways, handlers::add_railway, add_railway],
+    [delete, /api/v1/railways/:id, handlers::delete_railway, delete_railway]
+Vehicles records:
+    [get, /api/v1/vehicles, handlers::get_vehicles, get_vehicles],
+    [get, /api/v1/vehicles/:id, handlers::get_vehicle, get_vehicle],
+    [post, /api/v1/vehicles, handlers::add_vehicle, add_vehicle],
+    [delete, /api/v1/vehicles/:id, handlers::delete_vehicle, delete_vehicle]
+Road closures:
+    [get, /api/v1/closures, handlers::get_closures, get_closures],
+    [get, /api/v1/closures/active, handlers::get_active_closures, get_active_closures],
+    [post, /api/v1/closures, handlers::add_closure, add_closure],
+    [put, /api/v1/closures/:id, handlers::update_closure, update_closure],
+    [delete, /api/v1/closures/:id, handlers::delete_closure, delete_closure]
 Calculations && Algorithms:
-    Get shortest path from one City to Another (by Dijkstra algorithm)
-    [get, /api/v1/path, handlers::get_shortest_path, get_shortest_path]";
+    Get shortest path from one City to Another (by Dijkstra, A*, bidirectional Dijkstra or contraction hierarchy),
+    ?from=&to=[&via=][&algorithm=dijkstra|astar|bidirectional|ch][&optimize=distance|time|cost][&avoid_tolls=]
+    [&fuel_price=][&fuel_consumption=][&avoid_cities=][&avoid_roads=][&departure_time=][&vehicle=][&weight=][&height=]
+    [&hazmat=true|false][&mode=road|rail|any][&transfer_penalty=]
+    [&format=json|text]
+    [get, /api/v1/path, handlers::get_shortest_path, get_shortest_path]
+    Get up to k shortest alternative paths (by Yen algorithm), ?from=&to=[&k=3][&departure_time=]
+    [get, /api/v1/path/alternatives, handlers::get_alternative_paths, get_alternative_paths]
+    Get distance and predecessor of every City reachable from one City, ?from=[&departure_time=]
+    [get, /api/v1/path/tree, handlers::get_path_tree, get_path_tree]
+    Get Cities tagged as facilities
+    [get, /api/v1/facilities, handlers::get_facilities, get_facilities]
+    Get k facilities nearest to a City by road distance, ?from=[&k=3]
+    [get, /api/v1/facilities/nearest, handlers::get_nearest_facilities, get_nearest_facilities]
+    Get the nearest facility of every City (network Voronoi partition)
+    [get, /api/v1/facilities/assignment, handlers::get_facility_assignment, get_facility_assignment]
+    Get k Cities to open facilities in, minimising distance to the nearest facility (k-median),
+    ?k=[&weighted=true|false][&keep_existing=true|false][&candidates=]
+    [get, /api/v1/facilities/placement, handlers::get_facility_placement, get_facility_placement]
+    Get the shortest round trip through given cities (Held-Karp or 2-opt/Or-opt), body {start, stops}, [?departure_time=]
+    [post, /api/v1/tour, handlers::get_tour, get_tour]
+    Get every City within a distance budget, ?from=&max_distance=[&departure_time=]
+    [get, /api/v1/reachable, handlers::get_reachable, get_reachable]
+    Get shortest distances between sets of Cities, body {origins, destinations}, [?format=json|csv][&departure_time=]
+    [post, /api/v1/matrix, handlers::get_distance_matrix, get_distance_matrix]
+    Get connected components, bridges and articulation Cities of the road network
+    [get, /api/v1/graph/analysis, handlers::get_graph_analysis, get_graph_analysis]
+    Get minimum spanning forest of the road network (by Kruskal algorithm)
+    [get, /api/v1/graph/mst, handlers::get_spanning_tree, get_spanning_tree]";
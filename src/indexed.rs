@@ -0,0 +1,447 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use priority_queue::PriorityQueue;
+use crate::algorithm::{Edge, Graph};
+
+
+const INF: i32 = 10_i32.pow(9);
+
+// Nodes a witness search may settle before giving up and keeping the shortcut
+const WITNESS_SETTLE_LIMIT: usize = 200;
+
+
+/// Neighbour in an `IndexedGraph` adjacency list
+#[derive(Debug, Clone, Copy)]
+struct Link {
+    node: usize,
+    weight: i32,
+    edge: usize,
+}
+
+
+/// `Graph` with cities numbered in order of their names, searched without hashing or cloning strings
+#[derive(Debug)]
+pub struct IndexedGraph {
+    pub names: Vec<String>,
+    pub index: HashMap<String, usize>,
+    // Every edge of the source graph with the cities it connects
+    edges: Vec<(usize, usize, Edge)>,
+    outgoing: Vec<Vec<Link>>,
+    incoming: Vec<Vec<Link>>,
+}
+
+impl IndexedGraph {
+    pub fn from_graph(graph: &Graph) -> Self {
+        let mut names: Vec<String> = graph.keys().cloned().collect();
+        names.sort();
+        let index: HashMap<String, usize> = names.iter()
+            .enumerate()
+            .map(|(i, name)| (name.clone(), i))
+            .collect();
+
+        let mut edges = Vec::new();
+        let mut outgoing = vec![Vec::new(); names.len()];
+        let mut incoming = vec![Vec::new(); names.len()];
+        for (from, name) in names.iter().enumerate() {
+            for edge in &graph[name] {
+                let to = index[&edge.to];
+                let id = edges.len();
+                outgoing[from].push(Link { node: to, weight: edge.weight, edge: id });
+                incoming[to].push(Link { node: from, weight: edge.weight, edge: id });
+                edges.push((from, to, edge.clone()));
+            }
+        }
+        IndexedGraph { names, index, edges, outgoing, incoming }
+    }
+
+    /// Edges by id in the `trace_path` format
+    pub fn legs(&self, edges: &[usize]) -> Vec<(String, Edge)> {
+        edges.iter()
+            .map(|&id| {
+                let (from, _, edge) = &self.edges[id];
+                (self.names[*from].clone(), edge.clone())
+            })
+            .collect()
+    }
+
+    ///
+    /// # Bidirectional Dijkstra
+    /// Searches forward from `start` and backward from `goal` at once, stops when the two frontiers can't
+    /// improve the best meeting point any more.
+    ///
+    /// returns: (Option<Vec<usize>>, usize) => Ids of the edges of the shortest path, if any, and count of expanded nodes
+    ///
+    pub fn bidirectional_dijkstra(&self, start: usize, goal: usize) -> (Option<Vec<usize>>, usize) {
+        // Node -> (cost, edge it was reached by), forward and backward
+        let mut labels: [HashMap<usize, (i32, Option<usize>)>; 2] = [HashMap::new(), HashMap::new()];
+        let mut queues: [PriorityQueue<usize, Reverse<i32>>; 2] = [PriorityQueue::new(), PriorityQueue::new()];
+        labels[0].insert(start, (0, None));
+        labels[1].insert(goal, (0, None));
+        queues[0].push(start, Reverse(0));
+        queues[1].push(goal, Reverse(0));
+
+        let mut best = if start == goal { 0 } else { INF };
+        let mut meeting = if start == goal { Some(start) } else { None };
+        let mut expanded = 0;
+        loop {
+            let top = |side: usize| queues[side].peek().map_or(INF, |(_, Reverse(cost))| *cost);
+            let (forward, backward) = (top(0), top(1));
+            if forward.saturating_add(backward) >= best {
+                break;
+            }
+            let side = if forward <= backward { 0 } else { 1 };
+            let (node, Reverse(cost)) = queues[side].pop().unwrap();
+            expanded += 1;
+
+            let links = if side == 0 { &self.outgoing[node] } else { &self.incoming[node] };
+            for link in links {
+                let new_cost = cost.saturating_add(link.weight);
+                if labels[side].get(&link.node).is_some_and(|&(old, _)| old <= new_cost) {
+                    continue;
+                }
+                labels[side].insert(link.node, (new_cost, Some(link.edge)));
+                queues[side].push(link.node, Reverse(new_cost));
+                if let Some(&(other, _)) = labels[1 - side].get(&link.node) {
+                    if new_cost.saturating_add(other) < best {
+                        best = new_cost.saturating_add(other);
+                        meeting = Some(link.node);
+                    }
+                }
+            }
+        }
+
+        let meeting = match meeting {
+            Some(node) => node,
+            None => return (None, expanded),
+        };
+        let mut path = Vec::new();
+        let mut node = meeting;
+        while let Some((_, Some(edge))) = labels[0].get(&node) {
+            path.push(*edge);
+            node = self.edges[*edge].0;
+        }
+        path.reverse();
+        let mut node = meeting;
+        while let Some((_, Some(edge))) = labels[1].get(&node) {
+            path.push(*edge);
+            node = self.edges[*edge].1;
+        }
+        (Some(path), expanded)
+    }
+}
+
+
+/// What a hierarchy arc stands for
+#[derive(Debug, Clone, Copy)]
+enum Via {
+    // Edge of the `IndexedGraph`
+    Edge(usize),
+    // Two consecutive arcs around a contracted node
+    Shortcut(usize, usize),
+}
+
+#[derive(Debug, Clone, Copy)]
+struct HierarchyArc {
+    from: usize,
+    to: usize,
+    weight: i32,
+    via: Via,
+}
+
+
+///
+/// # Contraction hierarchy
+/// Nodes are contracted one by one, least important first, adding shortcuts that keep the distances
+/// between the remaining nodes. Queries then only ever go up the hierarchy, from both ends.
+///
+#[derive(Debug)]
+pub struct ContractionHierarchy {
+    arcs: Vec<HierarchyArc>,
+    // Node -> arcs to more important nodes
+    upward: Vec<Vec<usize>>,
+    // Node -> arcs from more important nodes
+    downward: Vec<Vec<usize>>,
+    pub shortcuts: usize,
+}
+
+// Graph being contracted, only arcs between nodes that are not contracted yet
+struct Contraction {
+    arcs: Vec<HierarchyArc>,
+    // Node -> neighbour -> arc, the cheapest one of parallel arcs
+    outgoing: Vec<HashMap<usize, usize>>,
+    incoming: Vec<HashMap<usize, usize>>,
+}
+
+impl Contraction {
+    fn add_arc(&mut self, from: usize, to: usize, weight: i32, via: Via) {
+        if let Some(&existing) = self.outgoing[from].get(&to) {
+            if self.arcs[existing].weight <= weight {
+                return;
+            }
+        }
+        let id = self.arcs.len();
+        self.arcs.push(HierarchyArc { from, to, weight, via });
+        self.outgoing[from].insert(to, id);
+        self.incoming[to].insert(from, id);
+    }
+
+    // Costs from `start` to nearby nodes avoiding `skip`, no further than `limit`
+    fn witness_search(&self, start: usize, skip: usize, limit: i32) -> HashMap<usize, i32> {
+        let mut costs = HashMap::new();
+        let mut pq: PriorityQueue<usize, Reverse<i32>> = PriorityQueue::new();
+        costs.insert(start, 0);
+        pq.push(start, Reverse(0));
+        let mut settled = 0;
+        while let Some((node, Reverse(cost))) = pq.pop() {
+            settled += 1;
+            if cost > limit || settled > WITNESS_SETTLE_LIMIT {
+                break;
+            }
+            for (&next, &arc) in &self.outgoing[node] {
+                let new_cost = cost.saturating_add(self.arcs[arc].weight);
+                if next == skip || costs.get(&next).is_some_and(|&old| old <= new_cost) {
+                    continue;
+                }
+                costs.insert(next, new_cost);
+                pq.push(next, Reverse(new_cost));
+            }
+        }
+        costs
+    }
+
+    // Shortcuts (from, to, weight, arc in, arc out) needed to contract `node`
+    fn shortcuts(&self, node: usize) -> Vec<(usize, usize, i32, usize, usize)> {
+        let mut shortcuts = Vec::new();
+        for (&from, &arc_in) in &self.incoming[node] {
+            let weight_in = self.arcs[arc_in].weight;
+            let limit = self.outgoing[node].values()
+                .map(|&arc_out| weight_in.saturating_add(self.arcs[arc_out].weight))
+                .max()
+                .unwrap_or(0);
+            let witnesses = self.witness_search(from, node, limit);
+            for (&to, &arc_out) in &self.outgoing[node] {
+                let weight = weight_in.saturating_add(self.arcs[arc_out].weight);
+                if to != from && witnesses.get(&to).is_none_or(|&cost| cost > weight) {
+                    shortcuts.push((from, to, weight, arc_in, arc_out));
+                }
+            }
+        }
+        shortcuts
+    }
+
+    // Contract cheap nodes first: few shortcuts for the arcs removed, and away from contracted neighbours
+    fn priority(&self, node: usize, contracted_neighbours: usize) -> i64 {
+        let removed = self.incoming[node].len() + self.outgoing[node].len();
+        self.shortcuts(node).len() as i64 - removed as i64 + contracted_neighbours as i64
+    }
+}
+
+impl ContractionHierarchy {
+    pub fn build(graph: &IndexedGraph) -> Self {
+        let n = graph.names.len();
+        let mut contraction = Contraction {
+            arcs: Vec::new(),
+            outgoing: vec![HashMap::new(); n],
+            incoming: vec![HashMap::new(); n],
+        };
+        for (id, (from, to, edge)) in graph.edges.iter().enumerate() {
+            if from != to {
+                contraction.add_arc(*from, *to, edge.weight, Via::Edge(id));
+            }
+        }
+
+        let mut contracted_neighbours = vec![0; n];
+        let mut queue: PriorityQueue<usize, Reverse<i64>> = PriorityQueue::new();
+        for node in 0..n {
+            queue.push(node, Reverse(contraction.priority(node, 0)));
+        }
+        let mut upward = vec![Vec::new(); n];
+        let mut downward = vec![Vec::new(); n];
+        let mut shortcuts = 0;
+        while let Some((node, _)) = queue.pop() {
+            // Priorities go stale as neighbours get contracted, recheck before contracting
+            let priority = contraction.priority(node, contracted_neighbours[node]);
+            if queue.peek().is_some_and(|(_, Reverse(next))| priority > *next) {
+                queue.push(node, Reverse(priority));
+                continue;
+            }
+
+            for (from, to, weight, arc_in, arc_out) in contraction.shortcuts(node) {
+                contraction.add_arc(from, to, weight, Via::Shortcut(arc_in, arc_out));
+                shortcuts += 1;
+            }
+            let outgoing = std::mem::take(&mut contraction.outgoing[node]);
+            let incoming = std::mem::take(&mut contraction.incoming[node]);
+            for &to in outgoing.keys() {
+                contraction.incoming[to].remove(&node);
+                contracted_neighbours[to] += 1;
+            }
+            for &from in incoming.keys() {
+                contraction.outgoing[from].remove(&node);
+                contracted_neighbours[from] += 1;
+            }
+            upward[node] = outgoing.into_values().collect();
+            downward[node] = incoming.into_values().collect();
+        }
+
+        ContractionHierarchy { arcs: contraction.arcs, upward, downward, shortcuts }
+    }
+
+    ///
+    /// Shortest path query, both searches only go up the hierarchy
+    ///
+    /// returns: (Option<Vec<usize>>, usize) => Ids of the `IndexedGraph` edges of the shortest path, if any,
+    /// and count of expanded nodes
+    ///
+    pub fn query(&self, start: usize, goal: usize) -> (Option<Vec<usize>>, usize) {
+        // Node -> (cost, arc it was reached by), forward and backward
+        let mut labels: [HashMap<usize, (i32, Option<usize>)>; 2] = [HashMap::new(), HashMap::new()];
+        let mut queues: [PriorityQueue<usize, Reverse<i32>>; 2] = [PriorityQueue::new(), PriorityQueue::new()];
+        labels[0].insert(start, (0, None));
+        labels[1].insert(goal, (0, None));
+        queues[0].push(start, Reverse(0));
+        queues[1].push(goal, Reverse(0));
+
+        let mut best = if start == goal { 0 } else { INF };
+        let mut meeting = if start == goal { Some(start) } else { None };
+        let mut expanded = 0;
+        loop {
+            // A side is done once nothing cheaper than the best meeting is left in its queue
+            let top = |side: usize| queues[side].peek().map_or(INF, |(_, Reverse(cost))| *cost);
+            let (forward, backward) = (top(0), top(1));
+            if forward >= best && backward >= best {
+                break;
+            }
+            let side = if forward <= backward { 0 } else { 1 };
+            let (node, Reverse(cost)) = queues[side].pop().unwrap();
+            expanded += 1;
+
+            let arcs = if side == 0 { &self.upward[node] } else { &self.downward[node] };
+            for &arc in arcs {
+                let next = if side == 0 { self.arcs[arc].to } else { self.arcs[arc].from };
+                let new_cost = cost.saturating_add(self.arcs[arc].weight);
+                if labels[side].get(&next).is_some_and(|&(old, _)| old <= new_cost) {
+                    continue;
+                }
+                labels[side].insert(next, (new_cost, Some(arc)));
+                queues[side].push(next, Reverse(new_cost));
+                if let Some(&(other, _)) = labels[1 - side].get(&next) {
+                    if new_cost.saturating_add(other) < best {
+                        best = new_cost.saturating_add(other);
+                        meeting = Some(next);
+                    }
+                }
+            }
+        }
+
+        let meeting = match meeting {
+            Some(node) => node,
+            None => return (None, expanded),
+        };
+        let mut arcs = Vec::new();
+        let mut node = meeting;
+        while let Some((_, Some(arc))) = labels[0].get(&node) {
+            arcs.push(*arc);
+            node = self.arcs[*arc].from;
+        }
+        arcs.reverse();
+        let mut node = meeting;
+        while let Some((_, Some(arc))) = labels[1].get(&node) {
+            arcs.push(*arc);
+            node = self.arcs[*arc].to;
+        }
+        (Some(self.unpack(&arcs)), expanded)
+    }
+
+    // Replace shortcuts with the edges they stand for
+    fn unpack(&self, arcs: &[usize]) -> Vec<usize> {
+        let mut edges = Vec::new();
+        let mut stack: Vec<usize> = arcs.iter().rev().copied().collect();
+        while let Some(arc) = stack.pop() {
+            match self.arcs[arc].via {
+                Via::Edge(edge) => edges.push(edge),
+                Via::Shortcut(first, second) => {
+                    stack.push(second);
+                    stack.push(first);
+                }
+            }
+        }
+        edges
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::{dijkstra, path_weight};
+    use crate::models::Mode;
+
+    // 4 x 4 grid with uneven weights and a few one-way streets
+    fn grid() -> Graph {
+        let name = |row: usize, column: usize| format!("{}{}", row, column);
+        let mut graph: Graph = HashMap::new();
+        let mut road_id = 0;
+        let mut link = |graph: &mut Graph, from: String, to: String, weight: i32, both_ways: bool| {
+            road_id += 1;
+            let edge = |to: &str| Edge { road_id, mode: Mode::Road, length: weight, duration: 0, toll: 0.0, weight, to: to.to_string() };
+            graph.entry(from.clone()).or_default().push(edge(&to));
+            graph.entry(to.clone()).or_default();
+            if both_ways {
+                graph.get_mut(&to).unwrap().push(edge(&from));
+            }
+        };
+        for row in 0..4 {
+            for column in 0..4 {
+                let weight = ((row * 7 + column * 3) % 5 + 1) as i32;
+                if column < 3 {
+                    link(&mut graph, name(row, column), name(row, column + 1), weight, row != 1);
+                }
+                if row < 3 {
+                    link(&mut graph, name(row, column), name(row + 1, column), weight + 1, column != 2);
+                }
+            }
+        }
+        graph
+    }
+
+    #[test]
+    fn indexed_searches_agree_with_dijkstra() {
+        let graph = grid();
+        let indexed = IndexedGraph::from_graph(&graph);
+        let hierarchy = ContractionHierarchy::build(&indexed);
+        for start in graph.keys() {
+            for goal in graph.keys() {
+                let expected = dijkstra(start, goal, &graph).0.get(goal).map(|(_, cost)| *cost);
+                let (s, t) = (indexed.index[start], indexed.index[goal]);
+                for (name, edges) in [("bidirectional", indexed.bidirectional_dijkstra(s, t).0), ("ch", hierarchy.query(s, t).0)] {
+                    let legs = edges.map(|edges| indexed.legs(&edges));
+                    if let Some(legs) = &legs {
+                        // Legs must chain from start to goal
+                        let mut at = start.clone();
+                        for (from, edge) in legs {
+                            assert_eq!(from, &at, "{} {} -> {}", name, start, goal);
+                            at = edge.to.clone();
+                        }
+                        assert_eq!(&at, goal, "{} {} -> {}", name, start, goal);
+                    }
+                    assert_eq!(legs.map(|legs| path_weight(&legs)), expected, "{} {} -> {}", name, start, goal);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn unreachable_goals_give_no_path() {
+        let edge = Edge { road_id: 1, mode: Mode::Road, length: 1, duration: 0, toll: 0.0, weight: 1, to: "B".to_string() };
+        let graph: Graph = [("A".to_string(), vec![edge]), ("B".to_string(), Vec::new()), ("C".to_string(), Vec::new())]
+            .into_iter()
+            .collect();
+        let indexed = IndexedGraph::from_graph(&graph);
+        let hierarchy = ContractionHierarchy::build(&indexed);
+        let (a, b, c) = (indexed.index["A"], indexed.index["B"], indexed.index["C"]);
+        assert!(indexed.bidirectional_dijkstra(b, a).0.is_none());
+        assert!(hierarchy.query(a, c).0.is_none());
+        assert!(hierarchy.query(a, b).0.is_some());
+    }
+}
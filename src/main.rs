@@ -13,6 +13,9 @@ mod handlers;
 mod commands;
 mod models;
 mod algorithm;
+mod analytics;
+mod cache;
+mod indexed;
 
 use commands::*;
 
@@ -27,10 +30,15 @@ fn main() {
         password=params.password
     );
 
+    let args: Vec<String> = std::env::args().collect();
+    // Runs on generated data, no database needed
+    if args.get(1).map(|s| s.as_str()) == Some("bench") {
+        return bench(&args);
+    }
+
     let mut db = Client::connect(&*connection_string, NoTls).unwrap();
     db::init_db(&mut db);
 
-    let args: Vec<String> = std::env::args().collect();
     match args.get(1) {
         Some(text) => {
             match text.as_ref() {
@@ -38,6 +46,9 @@ fn main() {
                 "del" => del(&mut db, &args),
                 "edit" => edit(&mut db, &args),
                 "show" => show(&mut db, &args),
+                "rail-add" => rail_add(&mut db, &args),
+                "rail-del" => rail_del(&mut db, &args),
+                "rail-show" => rail_show(&mut db, &args),
                 "help" => println!("{}", HELP),
                 command => panic!("Invalid command: {}", command),
             }
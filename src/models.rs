@@ -14,6 +14,41 @@ pub struct City {
     pub name: String,
     pub latitude: f32,
     pub longitude: f32,
+    // Depot or warehouse, found by the facility searches
+    #[serde(default)]
+    pub facility: bool,
+    // Weight of the city in population-weighted facility placement
+    #[serde(default)]
+    pub population: Option<i32>,
+}
+
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    #[default]
+    Both,
+    AToB,
+    BToA,
+}
+
+impl Direction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Direction::Both => "both",
+            Direction::AToB => "a_to_b",
+            Direction::BToA => "b_to_a",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Direction> {
+        match value {
+            "both" => Some(Direction::Both),
+            "a_to_b" => Some(Direction::AToB),
+            "b_to_a" => Some(Direction::BToA),
+            _ => None,
+        }
+    }
 }
 
 
@@ -22,14 +57,367 @@ pub struct Road {
     pub id: Option<i32>,
     pub city_a: i32,
     pub city_b: i32,
-    pub length: i32
+    pub length: i32,
+    #[serde(default)]
+    pub direction: Direction,
+    #[serde(default)]
+    pub road_class: Option<String>,
+    // km/h
+    #[serde(default)]
+    pub speed_limit: Option<i32>,
+    // Minutes, overrides the speed based estimate
+    #[serde(default)]
+    pub travel_time: Option<i32>,
+    // Money charged for driving the road
+    #[serde(default)]
+    pub toll: f64,
+    // Vehicle limits: tonnes, metres and whether hazardous goods may pass
+    #[serde(default)]
+    pub max_weight: Option<f64>,
+    #[serde(default)]
+    pub max_height: Option<f64>,
+    #[serde(default = "allowed")]
+    pub hazmat_allowed: bool,
 }
 
+fn allowed() -> bool {
+    true
+}
 
-#[derive(Serialize, Deserialize, Debug)]
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Railway {
     pub id: Option<i32>,
     pub city_a: i32,
     pub city_b: i32,
     pub length: i32
-}
\ No newline at end of file
+}
+
+
+// Road closed between two RFC 3339 timestamps, e.g. for roadworks
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Closure {
+    pub id: Option<i32>,
+    pub road_id: i32,
+    pub starts_at: String,
+    pub ends_at: String,
+    #[serde(default)]
+    pub reason: String,
+}
+
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Mode {
+    Road,
+    Rail,
+}
+
+impl Mode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Mode::Road => "road",
+            Mode::Rail => "rail",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Mode> {
+        match value {
+            "road" => Some(Mode::Road),
+            "rail" => Some(Mode::Rail),
+            _ => None,
+        }
+    }
+}
+
+
+// Named vehicle type of the fleet
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Vehicle {
+    pub id: Option<i32>,
+    pub name: String,
+    // Tonnes
+    #[serde(default)]
+    pub weight: Option<f64>,
+    // Metres
+    #[serde(default)]
+    pub height: Option<f64>,
+    #[serde(default)]
+    pub hazmat: bool,
+    // Litres per 100 km
+    #[serde(default)]
+    pub fuel_consumption: Option<f64>,
+    // km/h, the vehicle never goes faster on any road
+    #[serde(default)]
+    pub average_speed: Option<f64>,
+    #[serde(default = "road_only")]
+    pub modes: Vec<Mode>,
+}
+
+fn road_only() -> Vec<Mode> {
+    vec![Mode::Road]
+}
+
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Leg {
+    // Id of the road, or of the railway for rail legs
+    pub road_id: i32,
+    pub mode: Mode,
+    pub from: String,
+    pub to: String,
+    pub length: i32,
+    // Seconds
+    pub duration: i32,
+    pub toll: f64,
+}
+
+
+// Part of a route between two consecutive waypoints
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Segment {
+    pub from: String,
+    pub to: String,
+    pub legs: Vec<Leg>,
+    pub distance: i32,
+    pub duration: i32,
+}
+
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PathResponse {
+    pub from: String,
+    pub to: String,
+    pub via: Vec<String>,
+    pub path: Vec<City>,
+    pub legs: Vec<Leg>,
+    pub segments: Vec<Segment>,
+    pub distance: i32,
+    // Estimated travel time in seconds
+    pub duration: i32,
+    // Tolls along the route and the estimated total of fuel and tolls
+    pub toll: f64,
+    pub cost: f64,
+    pub optimize: String,
+    // Exclusions applied to the search
+    pub avoid_cities: Vec<String>,
+    pub avoid_roads: Vec<i32>,
+    // Moment the closures were checked at, now if not given
+    pub departure_time: Option<String>,
+    pub closed_roads: Vec<i32>,
+    pub vehicle_id: Option<i32>,
+    // Version of the cached network the route was found on, changes with every city, road or railway write
+    pub graph_version: u64,
+    pub algorithm: String,
+    pub mode: String,
+    pub nodes_expanded: usize,
+}
+
+
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AlternativeRoute {
+    pub path: Vec<City>,
+    pub legs: Vec<Leg>,
+    pub distance: i32,
+    // Length of roads shared with the best route, absolute and as a share of it
+    pub shared_length: i32,
+    pub overlap: f64,
+}
+
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AlternativesResponse {
+    pub from: String,
+    pub to: String,
+    pub routes: Vec<AlternativeRoute>,
+}
+
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TourRequest {
+    pub start: String,
+    pub stops: Vec<String>,
+}
+
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TourResponse {
+    pub start: String,
+    // Cities in visiting order, beginning and ending with `start`
+    pub order: Vec<String>,
+    pub method: String,
+    pub path: Vec<City>,
+    pub legs: Vec<Leg>,
+    pub segments: Vec<Segment>,
+    pub distance: i32,
+}
+
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ReachedCity {
+    pub city: City,
+    pub distance: i32,
+    // City and road the shortest path arrives from, none for the start city
+    pub predecessor: Option<String>,
+    pub road_id: Option<i32>,
+}
+
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ReachableResponse {
+    pub from: String,
+    pub max_distance: i32,
+    pub cities: Vec<ReachedCity>,
+}
+
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PathTreeResponse {
+    pub from: String,
+    pub graph_version: u64,
+    pub cities: Vec<ReachedCity>,
+}
+
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NearbyFacility {
+    pub city: City,
+    pub distance: i32,
+}
+
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NearestFacilitiesResponse {
+    pub from: String,
+    pub facilities: Vec<NearbyFacility>,
+}
+
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct FacilityAssignment {
+    pub city: String,
+    // Nearest facility and the distance to it, none if no facility can be reached
+    pub facility: Option<String>,
+    pub distance: Option<i32>,
+}
+
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AssignmentResponse {
+    pub facilities: Vec<String>,
+    pub cities: Vec<FacilityAssignment>,
+}
+
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PlacementResponse {
+    pub k: usize,
+    pub weighted: bool,
+    // Facilities kept open, the chosen cities are added to them
+    pub existing: Vec<String>,
+    pub chosen: Vec<String>,
+    // Sum of (population-weighted) distances from the cities to their nearest facility
+    pub total_distance: i64,
+    // Cities that count but can't reach any facility
+    pub unserved: usize,
+    pub cities: Vec<FacilityAssignment>,
+}
+
+
+// City given either by id or by name
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(untagged)]
+pub enum CityRef {
+    Id(i32),
+    Name(String),
+}
+
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MatrixRequest {
+    pub origins: Vec<CityRef>,
+    pub destinations: Vec<CityRef>,
+}
+
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MatrixResponse {
+    pub origins: Vec<String>,
+    pub destinations: Vec<String>,
+    // distances[i][j] from origins[i] to destinations[j], none if unreachable
+    pub distances: Vec<Vec<Option<i32>>>,
+}
+
+
+// Connected component a city belongs to
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CityComponent {
+    pub city: String,
+    pub component: usize,
+    pub component_size: usize,
+}
+
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UnreachableResponse {
+    pub error: String,
+    pub from: CityComponent,
+    pub to: CityComponent,
+}
+
+
+// Road limit a vehicle exceeds: `weight`, `height` or `hazmat`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Restriction {
+    pub road_id: i32,
+    pub constraint: String,
+    pub limit: Option<f64>,
+    pub vehicle: Option<f64>,
+}
+
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BlockedResponse {
+    pub error: String,
+    pub from: String,
+    pub to: String,
+    pub blocked_by: Restriction,
+}
+
+
+// Road whose closure splits its component
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Bridge {
+    pub road_id: i32,
+    pub city_a: String,
+    pub city_b: String,
+}
+
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GraphAnalysis {
+    pub component_count: usize,
+    // City names of every connected component, numbered as in `UnreachableResponse`
+    pub components: Vec<Vec<String>>,
+    pub bridges: Vec<Bridge>,
+    pub articulation_cities: Vec<String>,
+}
+
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SpanningTree {
+    pub cities: Vec<String>,
+    pub roads: Vec<i32>,
+    pub length: i32,
+}
+
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SpanningForest {
+    pub total_length: i32,
+    pub roads: Vec<i32>,
+    // Roads not needed to keep every component connected
+    pub redundant_roads: Vec<i32>,
+    pub trees: Vec<SpanningTree>,
+}
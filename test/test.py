@@ -70,11 +70,14 @@ def load_data():
             'city_a': 1,
             'city_b': 2,
             'length': 15,
+            'max_weight': 3.5,
         },
         {
             'city_a': 2,
             'city_b': 3,
             'length': 12,
+            'max_height': 4.0,
+            'hazmat_allowed': False,
         },
         {
             'city_a': 3,
@@ -85,6 +88,9 @@ def load_data():
             'city_a': 1,
             'city_b': 4,
             'length': 100,
+            'road_class': 'motorway',
+            'speed_limit': 130,
+            'toll': 5.5,
         },
         {
             'city_a': 1,
@@ -110,6 +116,12 @@ def load_data():
             'city_a': 6,
             'city_b': 7,
             'length': 21,
+        },
+        {
+            'city_a': 7,
+            'city_b': 2,
+            'length': 40,
+            'direction': 'a_to_b',
         }
     ]
     for road in roads:
@@ -117,6 +129,28 @@ def load_data():
     print(r.status_code)
     print('--Post roads--')
 
+    # Add railways
+    railways = [
+        {
+            'city_a': 2,
+            'city_b': 7,
+            'length': 50,
+        },
+        {
+            'city_a': 3,
+            'city_b': 5,
+            'length': 20,
+        }
+    ]
+    for railway in railways:
+        r = requests.post('http://localhost:3000/api/v1/railways', json=railway)
+    print(r.status_code)
+    r = requests.post('http://localhost:3000/api/v1/railways', json={'city_a': 1, 'city_b': 2, 'length': -5})
+    print(r.status_code, r.text)
+    r = requests.get('http://localhost:3000/api/v1/railways')
+    print(r.text)
+    print('--Post railways--')
+
 
 def get_path():
     r = requests.get('http://localhost:3000/')
@@ -127,16 +161,262 @@ def get_path():
 
     r = requests.get('http://localhost:3000/api/v1/path', params={'to': 'Habarovsk', 'from': 'Vladivostok'})
     print(r.status_code)
-    print(r.text.strip('"').replace('\\n', '\n'))
+    print(r.json())
     print('--Test 1--')
 
 
     r = requests.get('http://localhost:3000/api/v1/path', params={'to': 'Fokino', 'from': 'Vladivostok'})
     print(r.status_code)
-    print(r.text.strip('"').replace('\\n', '\n'))
+    print(r.json())
     print('--Test 2--')
 
 
+    r = requests.get('http://localhost:3000/api/v1/path',
+                     params={'to': 'Fokino', 'from': 'Vladivostok', 'format': 'text'})
+    print(r.status_code)
+    print(r.text.strip('"').replace('\\n', '\n'))
+    print('--Test 3--')
+
+
+    r = requests.get('http://localhost:3000/api/v1/path',
+                     params={'to': 'Fokino', 'from': 'Vladivostok', 'algorithm': 'astar'})
+    print(r.status_code)
+    print(r.json())
+    print('--Test 4--')
+
+
+    r = requests.get('http://localhost:3000/api/v1/path/alternatives',
+                     params={'to': 'Habarovsk', 'from': 'Vladivostok', 'k': 3})
+    print(r.status_code)
+    print([route['distance'] for route in r.json()['routes']])
+    print('--Test 5--')
+
+
+    r = requests.get('http://localhost:3000/api/v1/path',
+                     params={'to': 'Habarovsk', 'from': 'Vladivostok', 'via': 'Nahodka,Fokino'})
+    print(r.status_code)
+    print([(segment['from'], segment['to'], segment['distance']) for segment in r.json()['segments']])
+    print('--Test 6--')
+
+
+    r = requests.post('http://localhost:3000/api/v1/tour', json={
+        'start': 'Vladivostok',
+        'stops': ['Habarovsk', 'Fokino', 'Nahodka', 'Blagoveshensk']
+    })
+    print(r.status_code)
+    print(r.json()['order'], r.json()['distance'])
+    r = requests.post('http://localhost:3000/api/v1/tour', json={
+        'start': 'Vladivostok',
+        'stops': ['Stop {}'.format(i) for i in range(1000)]
+    })
+    print(r.status_code, r.text)
+    print('--Test 7--')
+
+
+    r = requests.get('http://localhost:3000/api/v1/reachable', params={'from': 'Vladivostok', 'max_distance': 40})
+    print(r.status_code)
+    print([(city['city']['name'], city['distance']) for city in r.json()['cities']])
+    print('--Test 8--')
+
+
+    matrix = {'origins': ['Vladivostok', 7], 'destinations': ['Fokino', 'Nahodka']}
+    r = requests.post('http://localhost:3000/api/v1/matrix', json=matrix)
+    print(r.status_code)
+    print(r.json()['distances'])
+    r = requests.post('http://localhost:3000/api/v1/matrix', params={'format': 'csv'}, json=matrix)
+    print(r.status_code)
+    print(r.text)
+    print('--Test 9--')
+
+
+    r = requests.get('http://localhost:3000/api/v1/path', params={'to': 'To-Delete', 'from': 'Vladivostok'})
+    print(r.status_code)
+    print(r.json())
+    print('--Test 10--')
+
+
+    r = requests.get('http://localhost:3000/api/v1/graph/analysis')
+    print(r.status_code)
+    print(r.json())
+    print('--Test 11--')
+
+
+    r = requests.get('http://localhost:3000/api/v1/graph/mst')
+    print(r.status_code)
+    print(r.json()['roads'], r.json()['total_length'])
+    print('--Test 12--')
+
+
+    r = requests.get('http://localhost:3000/api/v1/path',
+                     params={'to': 'Fokino', 'from': 'Vladivostok', 'mode': 'any', 'transfer_penalty': 0})
+    print(r.status_code)
+    print([(leg['mode'], leg['to']) for leg in r.json()['legs']], r.json()['distance'])
+    r = requests.get('http://localhost:3000/api/v1/path',
+                     params={'to': 'Fokino', 'from': 'Vladivostok', 'via': 'Artem', 'mode': 'any', 'transfer_penalty': 100})
+    print(r.status_code)
+    print([(leg['mode'], leg['to']) for leg in r.json()['legs']], r.json()['distance'])
+    print('--Test 13--')
+
+
+    r = requests.get('http://localhost:3000/api/v1/path', params={'to': 'Nahodka', 'from': 'Vlad', 'optimize': 'time'})
+    print(r.status_code)
+    print([leg['to'] for leg in r.json()['legs']], r.json()['distance'], r.json()['duration'])
+    r = requests.post('http://localhost:3000/api/v1/roads', json={'city_a': 1, 'city_b': 2, 'length': 10, 'travel_time': 10**9})
+    print(r.status_code, r.text)
+    r = requests.post('http://localhost:3000/api/v1/roads', json={'city_a': 1, 'city_b': 2, 'length': 10, 'toll': -1})
+    print(r.status_code, r.text)
+    r = requests.post('http://localhost:3000/api/v1/roads', json={'city_a': 1, 'city_b': 2, 'length': 10, 'max_weight': -5})
+    print(r.status_code, r.text)
+    print('--Test 14--')
+
+
+    r = requests.get('http://localhost:3000/api/v1/path',
+                     params={'to': 'Nahodka', 'from': 'Vlad', 'optimize': 'cost', 'fuel_price': 2, 'fuel_consumption': 10})
+    print(r.status_code)
+    print([leg['to'] for leg in r.json()['legs']], r.json()['toll'], r.json()['cost'])
+    r = requests.get('http://localhost:3000/api/v1/path',
+                     params={'to': 'Nahodka', 'from': 'Vlad', 'optimize': 'cost', 'fuel_price': 'inf'})
+    print(r.status_code, r.text)
+    print('--Test 15--')
+
+
+    r = requests.get('http://localhost:3000/api/v1/path', params={'to': 'Nahodka', 'from': 'Vlad', 'avoid_cities': 'Artem'})
+    print(r.status_code)
+    print([leg['to'] for leg in r.json()['legs']], r.json()['avoid_cities'], r.json()['avoid_roads'])
+    r = requests.get('http://localhost:3000/api/v1/path', params=[('from', 'Vlad'), ('from', 'Artem'), ('to', 'Nahodka')])
+    print(r.status_code, r.text)
+    print('--Test 16--')
+
+
+    r = requests.post('http://localhost:3000/api/v1/closures', json={
+        'road_id': 1,
+        'starts_at': '2030-01-01T00:00:00Z',
+        'ends_at': '2030-02-01T00:00:00Z',
+        'reason': 'Roadworks'
+    })
+    print(r.status_code)
+    r = requests.get('http://localhost:3000/api/v1/closures/active', params={'at': '2030-01-15T12:00:00Z'})
+    print(r.status_code)
+    print(r.json())
+    r = requests.get('http://localhost:3000/api/v1/path',
+                     params={'to': 'Artem', 'from': 'Vlad', 'departure_time': '2030-01-15T12:00:00Z'})
+    print(r.status_code)
+    print([leg['to'] for leg in r.json()['legs']], r.json()['closed_roads'])
+    r = requests.get('http://localhost:3000/api/v1/path/alternatives',
+                     params={'to': 'Artem', 'from': 'Vlad', 'departure_time': '2030-01-15T12:00:00Z'})
+    print(r.status_code)
+    print([[leg['road_id'] for leg in route['legs']] for route in r.json()['routes']])
+    r = requests.get('http://localhost:3000/api/v1/path', params={'to': 'Artem', 'from': 'Vlad', 'departure_time': '2030-02-30T00:00:00Z'})
+    print(r.status_code, r.text)
+    r = requests.post('http://localhost:3000/api/v1/closures', json={
+        'road_id': 1,
+        'starts_at': '2030-01-01T00:00:00Z',
+        'ends_at': 'tomorrow'
+    })
+    print(r.status_code, r.text)
+    print('--Test 17--')
+
+
+    r = requests.get('http://localhost:3000/api/v1/path', params={'to': 'Artem', 'from': 'Vlad', 'weight': 12})
+    print(r.status_code)
+    print([leg['to'] for leg in r.json()['legs']], r.json()['distance'])
+    r = requests.get('http://localhost:3000/api/v1/path',
+                     params={'to': 'Artem', 'from': 'Vladivostok', 'height': 5, 'avoid_roads': '4,5,6,7,8,9'})
+    print(r.status_code)
+    print(r.json())
+    print('--Test 18--')
+
+
+    r = requests.post('http://localhost:3000/api/v1/vehicles', json={
+        'name': 'Truck',
+        'weight': 12,
+        'fuel_consumption': 30,
+        'average_speed': 50
+    })
+    print(r.status_code)
+    r = requests.post('http://localhost:3000/api/v1/vehicles', json={'name': 'Truck'})
+    print(r.status_code, r.text)
+    r = requests.get('http://localhost:3000/api/v1/vehicles/999')
+    print(r.status_code, r.text)
+    r = requests.get('http://localhost:3000/api/v1/vehicles')
+    print(r.status_code)
+    print(r.json())
+    r = requests.get('http://localhost:3000/api/v1/path', params={'to': 'Artem', 'from': 'Vlad', 'vehicle': r.json()[0]['id']})
+    print(r.status_code)
+    print([leg['to'] for leg in r.json()['legs']], r.json()['duration'], r.json()['cost'])
+    print('--Test 19--')
+
+
+    version = requests.get('http://localhost:3000/api/v1/path', params={'to': 'Artem', 'from': 'Vlad'}).json()['graph_version']
+    r = requests.post('http://localhost:3000/api/v1/roads', json={'city_a': 1, 'city_b': 3, 'length': 5})
+    print(r.status_code)
+    r = requests.get('http://localhost:3000/api/v1/path', params={'to': 'Artem', 'from': 'Vlad'})
+    print(r.status_code)
+    print(r.json()['distance'], r.json()['graph_version'] > version)
+    print('--Test 20--')
+
+
+    for algorithm in ['dijkstra', 'bidirectional', 'ch']:
+        r = requests.get('http://localhost:3000/api/v1/path', params={'to': 'Nahodka', 'from': 'Vlad', 'algorithm': algorithm})
+        print(r.status_code)
+        print(algorithm, r.json()['distance'], [leg['to'] for leg in r.json()['legs']])
+    print('--Test 21--')
+
+
+    r = requests.get('http://localhost:3000/api/v1/path', params={'to': 'Nahodka', 'from': 'Vlad'})
+    print(r.status_code)
+    print(r.json()['algorithm'], r.json()['distance'], r.json()['nodes_expanded'])
+    print('--Test 22--')
+
+
+    r = requests.get('http://localhost:3000/api/v1/path/tree', params={'from': 'Vlad'})
+    print(r.status_code)
+    print([(city['city']['name'], city['distance'], city['predecessor']) for city in r.json()['cities']])
+    print('--Test 23--')
+
+
+    for city_id in [1, 4]:
+        r = requests.post('http://localhost:3000/api/v1/cities/{}/facility'.format(city_id))
+        print(r.status_code)
+    r = requests.get('http://localhost:3000/api/v1/facilities')
+    print(r.status_code)
+    print([city['name'] for city in r.json()])
+    r = requests.get('http://localhost:3000/api/v1/facilities/nearest', params={'from': 'Artem', 'k': 2})
+    print(r.status_code)
+    print([(facility['city']['name'], facility['distance']) for facility in r.json()['facilities']])
+    r = requests.get('http://localhost:3000/api/v1/facilities/assignment')
+    print(r.status_code)
+    print([(city['city'], city['facility'], city['distance']) for city in r.json()['cities']])
+    print('--Test 24--')
+
+
+    r = requests.get('http://localhost:3000/api/v1/facilities/placement', params={'k': 1})
+    print(r.status_code)
+    print(r.json()['existing'], r.json()['chosen'], r.json()['total_distance'], r.json()['unserved'])
+    r = requests.get('http://localhost:3000/api/v1/facilities/placement', params={'k': 2, 'keep_existing': 'false'})
+    print(r.status_code)
+    print(r.json()['existing'], r.json()['chosen'], r.json()['total_distance'], r.json()['unserved'])
+    r = requests.get('http://localhost:3000/api/v1/facilities/placement', params={'k': 1, 'weighted': 'true'})
+    print(r.status_code)
+    r = requests.get('http://localhost:3000/api/v1/facilities/placement', params={'k': 1, 'candidates': 'Artem,Fokino'})
+    print(r.status_code)
+    print(r.json()['chosen'], r.json()['total_distance'], r.json()['unserved'])
+    r = requests.post('http://localhost:3000/api/v1/cities', json={
+        'name': 'Ussuriysk',
+        'latitude': 24.4747,
+        'longitude': 25.6363,
+        'population': 170000
+    })
+    print(r.status_code)
+    city_id = requests.get('http://localhost:3000/api/v1/cities/Ussuriysk').json()['id']
+    r = requests.post('http://localhost:3000/api/v1/roads', json={'city_a': 7, 'city_b': city_id, 'length': 40})
+    print(r.status_code)
+    r = requests.get('http://localhost:3000/api/v1/facilities/placement', params={'k': 1, 'weighted': 'true'})
+    print(r.status_code)
+    print(r.json()['chosen'], r.json()['total_distance'], r.json()['unserved'])
+    print('--Test 25--')
+
+
 if __name__ == '__main__':
     test_connection()
     print("Connection trusted")